@@ -0,0 +1,114 @@
+//! Compares the size, accuracy and comparison speed of Bloom clocks against vector clocks on
+//! random histories. Run with `cargo bench --bench bloom_clock`.
+use std::{hint::black_box, time::Instant};
+
+use vector_clocks::{BloomClock, CausalOrdering, Result, Rng, VectorClock};
+
+/// Runs a random history where processes either record local events or receive a message from
+/// another process, and returns the final clocks of every process.
+fn random_history(
+  processes: usize,
+  steps: usize,
+  size: usize,
+  hashes: usize,
+) -> Result<Vec<(VectorClock, BloomClock)>> {
+  let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+  let mut clocks = (0..processes)
+    .map(|process_id| {
+      Ok((
+        VectorClock::new(process_id),
+        BloomClock::new(process_id, size, hashes)?,
+      ))
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+  for _ in 0..steps {
+    let i = rng.below(processes);
+    if rng.below(2) == 0 {
+      clocks[i].0.increment_this_process_clock()?;
+      clocks[i].1.increment()?;
+    } else {
+      let (vector_clock, bloom_clock) = clocks[rng.below(processes)].clone();
+      clocks[i].0.message_received(&vector_clock)?;
+      clocks[i].1.message_received(&bloom_clock)?;
+    }
+  }
+
+  Ok(clocks)
+}
+
+fn main() -> Result<()> {
+  println!(
+    "{:>9} {:>6} {:>6} | {:>10} {:>10} | {:>11} {:>12} {:>11} | {:>9} {:>9}",
+    "processes",
+    "cells",
+    "hashes",
+    "vc bytes",
+    "bloom bytes",
+    "concurrent",
+    "false pos.",
+    "estimated",
+    "vc ns",
+    "bloom ns"
+  );
+
+  for processes in [100, 1_000, 4_000] {
+    for (size, hashes) in [(128, 3), (1_024, 3)] {
+      let clocks = random_history(processes, processes * 10, size, hashes)?;
+
+      let vector_clock_bytes = clocks
+        .iter()
+        .map(|(vector_clock, _)| vector_clock.clocks().len() * 2 * std::mem::size_of::<u64>())
+        .sum::<usize>()
+        / processes;
+      let bloom_clock_bytes = size * std::mem::size_of::<u64>();
+
+      let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+      let pairs: Vec<(usize, usize)> = (0..10_000)
+        .map(|_| (rng.below(processes), rng.below(processes)))
+        .collect();
+
+      let mut concurrent = 0;
+      let mut false_positives = 0;
+      let mut estimated = 0.0;
+      for (i, j) in pairs.iter() {
+        let comparison = clocks[*i].1.compare(&clocks[*j].1)?;
+        if clocks[*i].0.compare(&clocks[*j].0) == CausalOrdering::Concurrent {
+          concurrent += 1;
+          if comparison.ordering != CausalOrdering::Concurrent {
+            false_positives += 1;
+          }
+        }
+        estimated += comparison.false_positive_probability;
+      }
+
+      let start = Instant::now();
+      for (i, j) in pairs.iter() {
+        black_box(clocks[*i].0.compare(&clocks[*j].0));
+      }
+      let vector_clock_nanos = start.elapsed().as_nanos() / pairs.len() as u128;
+
+      let start = Instant::now();
+      for (i, j) in pairs.iter() {
+        black_box(clocks[*i].1.compare(&clocks[*j].1)?);
+      }
+      let bloom_clock_nanos = start.elapsed().as_nanos() / pairs.len() as u128;
+
+      println!(
+        "{:>9} {:>6} {:>6} | {:>10} {:>10} | {:>11} {:>12} {:>11.4} | {:>9} {:>9}",
+        processes,
+        size,
+        hashes,
+        vector_clock_bytes,
+        bloom_clock_bytes,
+        concurrent,
+        false_positives,
+        estimated / pairs.len() as f64,
+        vector_clock_nanos,
+        bloom_clock_nanos
+      );
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,132 @@
+use vector_clocks::{CausalContext, MergeWith, Message, Process, Result, VectorClock};
+
+fn get(key: &str, p1: &Process, p2: &Process) -> Result<()> {
+  let v1 = p1.entry(key)?.context();
+  let v2 = p2.entry(key)?.context();
+
+  if !v1.is_concurrent_with(&v2) {
+    println!("ok. p1={:?} p2={:?}", v1.clocks(), v2.clocks(),);
+  } else {
+    println!(
+      "conflicting versions: p1={:?} p2={:?}",
+      v1.clocks(),
+      v2.clocks(),
+    );
+  }
+
+  Ok(())
+}
+
+fn main() -> Result<()> {
+  conflict_example_1()?;
+  no_conflict_example_1()?;
+  siblings_example_1()?;
+  merge_example_1()
+}
+
+fn conflict_example_1() -> Result<()> {
+  let mut p1 = Process::new(0);
+  let mut p2 = Process::new(1);
+
+  let key = "key1".to_owned();
+
+  p1.receive_message(Message {
+    vector_clock: VectorClock::new(0),
+    key: key.clone(),
+    value: Some("value 1".to_owned()),
+  })?;
+
+  let p1_context = p1.entry(&key)?.context();
+
+  p1.receive_message(Message {
+    vector_clock: p1_context,
+    key: key.clone(),
+    value: Some("value 2".to_owned()),
+  })?;
+
+  let p1_context = p1.entry(&key)?.context();
+
+  p1.receive_message(Message {
+    vector_clock: p1_context.clone(),
+    key: key.clone(),
+    value: Some("value 3".to_owned()),
+  })?;
+
+  p2.receive_message(Message {
+    vector_clock: p1_context,
+    key: key.clone(),
+    value: Some("value 3".to_owned()),
+  })?;
+
+  get(&key, &p1, &p2)
+}
+
+fn no_conflict_example_1() -> Result<()> {
+  let mut p1 = Process::new(0);
+  let mut p2 = Process::new(1);
+
+  let key = "key1".to_owned();
+
+  p1.receive_message(Message {
+    vector_clock: VectorClock::new(0),
+    key: key.clone(),
+    value: Some("value 1".to_owned()),
+  })?;
+
+  let p1_context = p1.entry(&key)?.context();
+
+  p1.receive_message(Message {
+    vector_clock: p1_context,
+    key: key.clone(),
+    value: Some("value 2".to_owned()),
+  })?;
+
+  let p1_context = p1.entry(&key)?.context();
+
+  p2.receive_message(Message {
+    vector_clock: p1_context,
+    key: key.clone(),
+    value: Some("value 3".to_owned()),
+  })?;
+
+  get(&key, &p1, &p2)
+}
+
+fn siblings_example_1() -> Result<()> {
+  let mut p1: Process = Process::new(0);
+
+  let key = "key1";
+
+  // Two clients write to the same key without having seen each other's write.
+  p1.put(key, "value 1".to_owned(), CausalContext::default())?;
+  p1.put(key, "value 2".to_owned(), CausalContext::default())?;
+
+  let result = p1.get(key)?;
+  println!("siblings: {:?}", result.values);
+
+  // A client that has read both values writes with their context, superseding them.
+  p1.put(key, "value 3".to_owned(), result.context)?;
+
+  println!("after resolving: {:?}", p1.get(key)?.values);
+
+  Ok(())
+}
+
+fn merge_example_1() -> Result<()> {
+  let p1: Process = Process::new(0);
+  let mut p1 = p1.with_resolver(MergeWith(|_key: &str, values: &[&String]| {
+    let mut values: Vec<&str> = values.iter().map(|value| value.as_str()).collect();
+    values.sort();
+    values.join(",")
+  }));
+
+  let key = "key1";
+
+  // Concurrent writes are merged as soon as they're detected.
+  p1.put(key, "value 1".to_owned(), CausalContext::default())?;
+  p1.put(key, "value 2".to_owned(), CausalContext::default())?;
+
+  println!("merged: {:?}", p1.get(key)?.values);
+
+  Ok(())
+}
@@ -0,0 +1,436 @@
+//! A process of the replicated key-value store, serving requests over TCP.
+//!
+//! Every process accepts [`Request`]s from clients and from the other processes of the cluster.
+//! Writes coordinated by a process are replicated to its peers as soon as they're applied, and
+//! every process periodically sends all of its entries to its peers so they converge even if
+//! some replication requests are lost.
+//!
+//! A cluster of three processes can be run on one machine with:
+//!
+//! ```text
+//! cargo run --bin node -- serve 0 127.0.0.1:7000 1=127.0.0.1:7001 2=127.0.0.1:7002
+//! cargo run --bin node -- serve 1 127.0.0.1:7001 0=127.0.0.1:7000 2=127.0.0.1:7002
+//! cargo run --bin node -- serve 2 127.0.0.1:7002 0=127.0.0.1:7000 1=127.0.0.1:7001
+//! ```
+//!
+//! and used with:
+//!
+//! ```text
+//! cargo run --bin node -- put 127.0.0.1:7000 key value
+//! cargo run --bin node -- get 127.0.0.1:7001 key
+//! cargo run --bin node -- put 127.0.0.1:7002 key other-value <context printed by get>
+//! cargo run --bin node -- delete 127.0.0.1:7000 key <context printed by get>
+//! ```
+//!
+//! Processes keep their values in memory unless they're given a write-ahead log with
+//! `--log <path>`, which is replayed when they restart and compacted into a snapshot once it
+//! grows.
+//!
+//! Clients that need read-your-writes and monotonic reads across processes use the session
+//! methods of [`Client`]. A process that hasn't caught up with the session of a read waits for
+//! replication to bring it up to date, and rejects the read if that takes too long.
+//!
+//! Contexts are printed and parsed as the hexadecimal [`WireFormat`] encoding of a
+//! [`CausalContext`].
+
+use std::{
+  collections::BTreeMap,
+  net::{SocketAddr, TcpListener, TcpStream},
+  sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc, Condvar, Mutex,
+  },
+  thread,
+  time::Duration,
+};
+
+use vector_clocks::{
+  read_frame, write_frame, CausalContext, Client, CompactionPolicy, DottedVersionVector, Error,
+  FsyncPolicy, KeepSiblings, Log, LogRecord, Process, Request, Response, Result, Snapshot,
+  SystemClock, WireFormat, WriteAheadLog,
+};
+
+/// Values are versioned with dotted version vectors, so concurrent writes coordinated by the same
+/// process are kept as siblings.
+type Version = DottedVersionVector;
+
+type Node = Process<usize, u64, KeepSiblings, Version, SystemClock, NodeLog>;
+
+/// The log of the process: nothing, or a write-ahead log when `--log` is given.
+enum NodeLog {
+  Volatile,
+  WriteAheadLog(WriteAheadLog),
+}
+
+impl Log<usize, u64, Version> for NodeLog {
+  fn records(&mut self) -> Result<Vec<LogRecord<usize, u64, Version>>> {
+    match self {
+      NodeLog::Volatile => Ok(Vec::new()),
+      NodeLog::WriteAheadLog(log) => log.records(),
+    }
+  }
+
+  fn append(&mut self, record: &LogRecord<usize, u64, Version>) -> Result<()> {
+    match self {
+      NodeLog::Volatile => Ok(()),
+      NodeLog::WriteAheadLog(log) => log.append(record),
+    }
+  }
+
+  fn latest_snapshot(&mut self) -> Result<Option<Snapshot<usize, u64, Version>>> {
+    match self {
+      NodeLog::Volatile => Ok(None),
+      NodeLog::WriteAheadLog(log) => log.latest_snapshot(),
+    }
+  }
+
+  fn should_compact(&self) -> bool {
+    match self {
+      NodeLog::Volatile => false,
+      NodeLog::WriteAheadLog(log) => Log::<usize, u64, Version>::should_compact(log),
+    }
+  }
+
+  fn compact(&mut self, snapshot: &Snapshot<usize, u64, Version>) -> Result<()> {
+    match self {
+      NodeLog::Volatile => Ok(()),
+      NodeLog::WriteAheadLog(log) => log.compact(snapshot),
+    }
+  }
+}
+
+/// The process, shared by the threads serving connections.
+struct State {
+  node: Mutex<Node>,
+  /// Notified whenever an entry changes, so reads in a session waiting for the process to catch
+  /// up can check again.
+  changed: Condvar,
+}
+
+/// How often every entry is sent to every peer.
+const ANTI_ENTROPY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a read in a session waits for the process to catch up with the session before it's
+/// rejected.
+const SESSION_READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many records the write-ahead log holds before it's compacted into a snapshot.
+const LOG_COMPACTION_RECORDS: usize = 10_000;
+
+/// How long to wait before connecting again to a peer that couldn't be reached.
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+const USAGE: &str = "usage:
+  node serve <process id> <listen address> [--log <path>] [<peer process id>=<peer address>]...
+  node get <address> <key>
+  node put <address> <key> <value> [<context>]
+  node delete <address> <key> [<context>]";
+
+fn main() {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+  let result = match args.as_slice() {
+    ["serve", process_id, address, peers @ ..] => serve(process_id, address, peers),
+    ["get", address, key] => get(address, key),
+    ["put", address, key, value] => put(address, key, value, None),
+    ["put", address, key, value, context] => put(address, key, value, Some(context)),
+    ["delete", address, key] => delete(address, key, None),
+    ["delete", address, key, context] => delete(address, key, Some(context)),
+    _ => {
+      eprintln!("{USAGE}");
+      std::process::exit(2);
+    }
+  };
+
+  if let Err(error) = result {
+    eprintln!("error: {error}");
+    std::process::exit(1);
+  }
+}
+
+fn serve(process_id: &str, address: &str, mut peers: &[&str]) -> Result<()> {
+  let process_id = parse_process_id(process_id)?;
+  let address = parse_address(address)?;
+  let log = match peers {
+    ["--log", path, rest @ ..] => {
+      peers = rest;
+      NodeLog::WriteAheadLog(
+        WriteAheadLog::open(path, FsyncPolicy::Always)?
+          .with_compaction_policy(CompactionPolicy::Records(LOG_COMPACTION_RECORDS)),
+      )
+    }
+    _ => NodeLog::Volatile,
+  };
+  let peers = peers
+    .iter()
+    .map(|peer| {
+      let (peer_id, peer_address) = peer.split_once('=').ok_or_else(|| {
+        Error::InvalidConfiguration(format!("peer {peer:?} is not <process id>=<address>"))
+      })?;
+      Ok((parse_process_id(peer_id)?, parse_address(peer_address)?))
+    })
+    .collect::<Result<BTreeMap<_, _>>>()?;
+
+  let node = Process::with_members(process_id, peers.keys().copied()).with_log(log)?;
+  let state = Arc::new(State {
+    node: Mutex::new(node),
+    changed: Condvar::new(),
+  });
+
+  let replicas: Vec<_> = peers
+    .into_iter()
+    .filter(|(peer_id, _)| *peer_id != process_id)
+    .map(|(peer_id, peer_address)| {
+      let (sender, receiver) = mpsc::channel();
+      thread::spawn(move || replicate(peer_id, peer_address, receiver));
+      sender
+    })
+    .collect();
+  let replicas = Arc::new(replicas);
+
+  {
+    let state = Arc::clone(&state);
+    let replicas = Arc::clone(&replicas);
+    thread::spawn(move || anti_entropy(&state, &replicas));
+  }
+
+  let listener = TcpListener::bind(address)?;
+  eprintln!("process {process_id} listening on {address}");
+
+  for stream in listener.incoming() {
+    let stream = match stream {
+      Ok(stream) => stream,
+      Err(error) => {
+        eprintln!("failed to accept connection: {error}");
+        continue;
+      }
+    };
+    let state = Arc::clone(&state);
+    let replicas = Arc::clone(&replicas);
+    thread::spawn(move || {
+      if let Err(error) = handle_connection(stream, &state, &replicas) {
+        eprintln!("connection closed: {error}");
+      }
+    });
+  }
+
+  Ok(())
+}
+
+/// Serves the requests sent over `stream` until it's closed.
+fn handle_connection(
+  mut stream: TcpStream,
+  state: &State,
+  replicas: &[Sender<Request<usize, u64, Version>>],
+) -> Result<()> {
+  stream.set_nodelay(true)?;
+
+  loop {
+    let request = match read_frame(&mut stream) {
+      Ok(Some(request)) => request,
+      Ok(None) => return Ok(()),
+      Err(error) => {
+        // The stream cannot be trusted after a malformed frame, so the connection is closed.
+        let response: Response = Response::Error(error.clone());
+        let _ = write_frame(&mut stream, &response);
+        return Err(error);
+      }
+    };
+
+    let response = handle_request(request, state, replicas).unwrap_or_else(Response::Error);
+    write_frame(&mut stream, &response)?;
+  }
+}
+
+fn handle_request(
+  request: Request<usize, u64, Version>,
+  state: &State,
+  replicas: &[Sender<Request<usize, u64, Version>>],
+) -> Result<Response> {
+  let mut node = state.node.lock().unwrap();
+
+  let (key, response) = match request {
+    Request::Get { key } => return node.get(&key).map(Response::Values),
+    Request::SessionGet { key, mut session } => {
+      // Reads wait for replication to bring the process up to date with the session, and are
+      // rejected if it doesn't within the timeout.
+      let (node, _) = state
+        .changed
+        .wait_timeout_while(node, SESSION_READ_TIMEOUT, |node| {
+          !node.has_caught_up(&key, &session)
+        })
+        .unwrap();
+      let result = node.session_get(&key, &mut session)?;
+      return Ok(Response::SessionValues(result, session));
+    }
+    Request::Put {
+      key,
+      value,
+      context,
+    } => {
+      let context = node.put(&key, value, context)?;
+      (key, Response::Context(context))
+    }
+    Request::SessionPut {
+      key,
+      value,
+      context,
+      mut session,
+    } => {
+      let context = node.session_put(&key, value, context, &mut session)?;
+      (key, Response::SessionContext(context, session))
+    }
+    Request::Delete { key, context } => {
+      let context = node.delete(&key, context)?;
+      (key, Response::Context(context))
+    }
+    Request::SessionDelete {
+      key,
+      context,
+      mut session,
+    } => {
+      let context = node.session_delete(&key, context, &mut session)?;
+      (key, Response::SessionContext(context, session))
+    }
+    Request::Message(message) => {
+      let key = message.key.clone();
+      node.receive_message(message)?;
+      (key, Response::Done)
+    }
+    Request::Replicate { key, entry } => {
+      node.merge_entry(&key, &entry)?;
+      state.changed.notify_all();
+      return Ok(Response::Done);
+    }
+  };
+  state.changed.notify_all();
+
+  // Writes coordinated by this process are sent to its peers right away. Entries merged from
+  // other processes are not, the process that coordinated them already did.
+  let entry = node.entry(&key)?;
+  for replica in replicas {
+    let _ = replica.send(Request::Replicate {
+      key: key.clone(),
+      entry: entry.clone(),
+    });
+  }
+
+  Ok(response)
+}
+
+/// Sends the requests received from `requests` to the peer, until every sender is dropped.
+///
+/// Requests that cannot be delivered are dropped, anti-entropy delivers their entries later.
+fn replicate(
+  peer_id: usize,
+  peer_address: SocketAddr,
+  requests: Receiver<Request<usize, u64, Version>>,
+) {
+  let mut client: Option<Client> = None;
+
+  while let Ok(request) = requests.recv() {
+    if client.is_none() {
+      match Client::connect(peer_address) {
+        Ok(connected) => client = Some(connected),
+        Err(error) => {
+          eprintln!("failed to connect to process {peer_id}: {error}");
+          thread::sleep(RECONNECT_DELAY);
+          // Requests that piled up while the peer was unreachable are superseded by the next
+          // round of anti-entropy.
+          while requests.try_recv().is_ok() {}
+          continue;
+        }
+      }
+    }
+
+    if let Some(connected) = client.as_mut() {
+      if let Err(error) = connected.send(&request) {
+        eprintln!("failed to replicate to process {peer_id}: {error}");
+        if matches!(error, Error::Io(_)) {
+          client = None;
+        }
+      }
+    }
+  }
+}
+
+/// Periodically sends every entry to every peer.
+fn anti_entropy(state: &State, replicas: &[Sender<Request<usize, u64, Version>>]) {
+  loop {
+    thread::sleep(ANTI_ENTROPY_INTERVAL);
+
+    let node = state.node.lock().unwrap();
+    for key in node.keys() {
+      let entry = node.entry(key).expect("listed keys have an entry");
+      for replica in replicas {
+        let _ = replica.send(Request::Replicate {
+          key: key.clone(),
+          entry: entry.clone(),
+        });
+      }
+    }
+  }
+}
+
+fn get(address: &str, key: &str) -> Result<()> {
+  let mut client: Client = Client::connect(parse_address(address)?)?;
+  let result = client.get(key)?;
+  for value in &result.values {
+    println!("{value}");
+  }
+  println!("context: {}", encode_context(&result.context));
+  Ok(())
+}
+
+fn put(address: &str, key: &str, value: &str, context: Option<&str>) -> Result<()> {
+  let mut client: Client = Client::connect(parse_address(address)?)?;
+  let context = client.put(key, value.to_owned(), decode_context(context)?)?;
+  println!("context: {}", encode_context(&context));
+  Ok(())
+}
+
+fn delete(address: &str, key: &str, context: Option<&str>) -> Result<()> {
+  let mut client: Client = Client::connect(parse_address(address)?)?;
+  let context = client.delete(key, decode_context(context)?)?;
+  println!("context: {}", encode_context(&context));
+  Ok(())
+}
+
+fn parse_process_id(process_id: &str) -> Result<usize> {
+  process_id
+    .parse()
+    .map_err(|_| Error::InvalidProcessId(process_id.to_owned()))
+}
+
+fn parse_address(address: &str) -> Result<SocketAddr> {
+  address
+    .parse()
+    .map_err(|_| Error::InvalidConfiguration(format!("{address:?} is not a socket address")))
+}
+
+fn encode_context(context: &CausalContext) -> String {
+  context
+    .to_bytes()
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect()
+}
+
+fn decode_context(context: Option<&str>) -> Result<CausalContext> {
+  let Some(context) = context else {
+    return Ok(CausalContext::default());
+  };
+
+  let invalid = || Error::InvalidEncoding(format!("{context:?} is not a hexadecimal context"));
+  if context.len() % 2 != 0 {
+    return Err(invalid());
+  }
+  let bytes = (0..context.len())
+    .step_by(2)
+    .map(|i| {
+      u8::from_str_radix(context.get(i..i + 2).ok_or_else(invalid)?, 16).map_err(|_| invalid())
+    })
+    .collect::<Result<Vec<_>>>()?;
+  CausalContext::from_bytes(&bytes)
+}
@@ -0,0 +1,235 @@
+use std::hash::{Hash, Hasher};
+
+use crate::{CausalOrdering, Error, LogicalClock, ProcessId, Result};
+
+/// The result of comparing two [`BloomClock`]s.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BloomComparison {
+  /// The causal ordering suggested by the clocks. [`CausalOrdering::Concurrent`] is always right,
+  /// the other orderings may be false positives.
+  pub ordering: CausalOrdering,
+  /// The estimated probability that `ordering` is a false positive and the events are actually
+  /// concurrent.
+  pub false_positive_probability: f64,
+}
+
+/// A [Bloom clock][BloomClocks] is a counting Bloom filter of the events seen by a process. Its
+/// size is fixed no matter how many processes are part of the system, at the cost of comparisons
+/// that may report ordered events when they are concurrent.
+///
+/// Each event is hashed into `hashes` of the cells of the clock, whose counters are incremented.
+/// The hash function is fixed, so that processes built with different versions of Rust or for
+/// different platforms agree on the cells of an event.
+/// A clock whose counters are all smaller or equal than the counters of another clock has likely
+/// seen a subset of its events; the probability that it hasn't grows with the number of events
+/// only the other clock has seen, relative to the number of cells.
+///
+/// [BloomClocks]: https://arxiv.org/abs/1905.13064
+#[derive(Debug, Clone)]
+pub struct BloomClock<P = usize> {
+  /// The id of the process that owns the clock.
+  process_id: P,
+  /// How many cells each event is hashed into.
+  hashes: usize,
+  cells: Vec<u64>,
+  /// How many events happened in the process that owns the clock, used to tell its events apart.
+  events: u64,
+}
+
+impl<P: ProcessId> BloomClock<P> {
+  /// Creates a clock owned by `process_id` with `size` cells, each event being hashed into
+  /// `hashes` of them. Every process must use the same size and number of hashes.
+  ///
+  /// Fails if `size` or `hashes` is zero.
+  pub fn new(process_id: P, size: usize, hashes: usize) -> Result<Self> {
+    if size == 0 || hashes == 0 {
+      return Err(Error::InvalidConfiguration(format!(
+        "bloom clocks need at least one cell and one hash, got {size} cells and {hashes} hashes"
+      )));
+    }
+
+    Ok(Self {
+      process_id,
+      hashes,
+      cells: vec![0; size],
+      events: 0,
+    })
+  }
+
+  /// Returns the id of the process that owns this clock.
+  pub fn process_id(&self) -> &P {
+    &self.process_id
+  }
+
+  /// Returns the counter of each cell.
+  pub fn cells(&self) -> &[u64] {
+    &self.cells
+  }
+
+  /// Returns how many cells each event is hashed into.
+  pub fn hashes(&self) -> usize {
+    self.hashes
+  }
+
+  /// Records an event that happened in the process that owns this clock.
+  ///
+  /// Fails if a counter would overflow, in which case the clock is left untouched.
+  pub fn increment(&mut self) -> Result<()> {
+    let overflow = || Error::CounterOverflow(format!("{:?}", self.process_id));
+
+    let events = self.events.checked_add(1).ok_or_else(overflow)?;
+    let mut cells = self.cells.clone();
+    for i in 0..self.hashes {
+      let mut hasher = StableHasher::default();
+      (&self.process_id, events, i).hash(&mut hasher);
+      let cell = &mut cells[(hasher.finish() % self.cells.len() as u64) as usize];
+      *cell = cell.checked_add(1).ok_or_else(overflow)?;
+    }
+
+    self.cells = cells;
+    self.events = events;
+    Ok(())
+  }
+
+  /// Records the receipt of a message sent with `other_process_bloom_clock`.
+  ///
+  /// Fails if the clocks have different sizes or if a counter would overflow, in which case the
+  /// clock is left untouched.
+  pub fn message_received(&mut self, other_process_bloom_clock: &BloomClock<P>) -> Result<()> {
+    let mut clock = self.clone();
+    clock.join_in_place(other_process_bloom_clock)?;
+    clock.increment()?;
+    *self = clock;
+    Ok(())
+  }
+
+  /// Merges the events of `other` into `self` without counting it as an event.
+  ///
+  /// Fails if the clocks have different sizes, in which case the clock is left untouched.
+  pub fn join_in_place(&mut self, other: &BloomClock<P>) -> Result<()> {
+    self.check_size(other)?;
+    for (cell, other_cell) in self.cells.iter_mut().zip(other.cells.iter()) {
+      *cell = std::cmp::max(*cell, *other_cell);
+    }
+    Ok(())
+  }
+
+  /// Compares the events seen by `self` and `other`, with the probability that the ordering is a
+  /// false positive.
+  ///
+  /// Fails if the clocks have different sizes.
+  pub fn compare(&self, other: &BloomClock<P>) -> Result<BloomComparison> {
+    self.check_size(other)?;
+
+    let mut some_smaller = false;
+    let mut some_greater = false;
+    for (cell, other_cell) in self.cells.iter().zip(other.cells.iter()) {
+      match cell.cmp(other_cell) {
+        std::cmp::Ordering::Less => some_smaller = true,
+        std::cmp::Ordering::Greater => some_greater = true,
+        std::cmp::Ordering::Equal => {}
+      }
+    }
+
+    let ordering = match (some_smaller, some_greater) {
+      (false, false) => CausalOrdering::Equal,
+      (true, false) => CausalOrdering::Before,
+      (false, true) => CausalOrdering::After,
+      (true, true) => CausalOrdering::Concurrent,
+    };
+
+    let false_positive_probability = match ordering {
+      CausalOrdering::Before => self.false_positive_probability(other),
+      CausalOrdering::After => other.false_positive_probability(self),
+      CausalOrdering::Equal | CausalOrdering::Concurrent => 0.0,
+    };
+
+    Ok(BloomComparison {
+      ordering,
+      false_positive_probability,
+    })
+  }
+
+  /// Returns the probability that every cell of an event of `self` is covered by the increments
+  /// only `other` has, given that `other` dominates `self`.
+  fn false_positive_probability(&self, other: &BloomClock<P>) -> f64 {
+    let increments = other.cells.iter().sum::<u64>() - self.cells.iter().sum::<u64>();
+    let cells = self.cells.len() as f64;
+    let cell_covered = 1.0 - (1.0 - 1.0 / cells).powf(increments as f64);
+    cell_covered.powi(self.hashes as i32)
+  }
+
+  fn check_size(&self, other: &BloomClock<P>) -> Result<()> {
+    if self.cells.len() != other.cells.len() || self.hashes != other.hashes {
+      return Err(Error::InvalidConfiguration(format!(
+        "cannot combine a bloom clock of {} cells and {} hashes with one of {} cells and {} hashes",
+        self.cells.len(),
+        self.hashes,
+        other.cells.len(),
+        other.hashes
+      )));
+    }
+    Ok(())
+  }
+}
+
+impl<P: ProcessId> LogicalClock for BloomClock<P> {
+  fn tick(&mut self) -> Result<()> {
+    self.increment()
+  }
+
+  fn receive(&mut self, other: &Self) -> Result<()> {
+    self.message_received(other)
+  }
+
+  /// Compares the clocks, treating clocks of different sizes as concurrent.
+  fn compare(&self, other: &Self) -> CausalOrdering {
+    BloomClock::compare(self, other)
+      .map(|comparison| comparison.ordering)
+      .unwrap_or(CausalOrdering::Concurrent)
+  }
+}
+
+/// A [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hasher whose output goes through the
+/// splitmix64 finalizer, so that every bit of the input affects the cell picked by the low bits.
+/// Unlike [`DefaultHasher`](std::collections::hash_map::DefaultHasher), its output doesn't change
+/// between versions of Rust, and integers are hashed as little endian 64 bit numbers so that it
+/// doesn't change between platforms either.
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+  fn default() -> Self {
+    Self(0xcbf2_9ce4_8422_2325)
+  }
+}
+
+impl Hasher for StableHasher {
+  fn write(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+    }
+  }
+
+  fn write_u16(&mut self, i: u16) {
+    self.write_u64(i.into());
+  }
+
+  fn write_u32(&mut self, i: u32) {
+    self.write_u64(i.into());
+  }
+
+  fn write_u64(&mut self, i: u64) {
+    self.write(&i.to_le_bytes());
+  }
+
+  fn write_usize(&mut self, i: usize) {
+    self.write_u64(i as u64);
+  }
+
+  fn finish(&self) -> u64 {
+    let mut z = self.0;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+}
@@ -0,0 +1,150 @@
+use crate::{Counter, Error, Message, ProcessId, Result, VectorClock};
+
+/// A payload broadcast to every process, stamped with the vector clock of its sender.
+#[derive(Debug, Clone)]
+pub struct Broadcast<P = usize, C = u64, T = Message<P, C>> {
+  /// The broadcasts delivered by the sender before sending this one, plus this one. The clock is
+  /// owned by the sender.
+  pub vector_clock: VectorClock<P, C>,
+  pub payload: T,
+}
+
+impl<P: ProcessId, C: Counter, T> Broadcast<P, C, T> {
+  /// Returns the id of the process that sent the broadcast.
+  pub fn sender(&self) -> &P {
+    self.vector_clock.process_id()
+  }
+}
+
+/// Delivers broadcasts in causal order, following the protocol of Birman, Schiper and
+/// Stephenson: a broadcast is only delivered once every broadcast its sender had delivered before
+/// sending it has been delivered, and the broadcasts that arrive too early are held back until
+/// then.
+///
+/// The clock of the layer counts the broadcasts delivered from each process. A broadcast from
+/// process `j` is deliverable when its clock is one ahead for `j` and not ahead for any other
+/// process.
+///
+/// Payloads are usually [`Message`]s handed to
+/// [`Process::receive_message`](crate::Process::receive_message) once delivered, so that no
+/// process applies a write before the writes it depends on.
+#[derive(Debug)]
+pub struct CausalBroadcast<P = usize, C = u64, T = Message<P, C>> {
+  /// Counts the broadcasts delivered from each process, including this one.
+  vector_clock: VectorClock<P, C>,
+  /// Broadcasts received before their causal predecessors, in arrival order.
+  held_back: Vec<Broadcast<P, C, T>>,
+  /// How many broadcasts can be held back at once.
+  capacity: usize,
+}
+
+impl<P: ProcessId, C: Counter, T> CausalBroadcast<P, C, T> {
+  /// Creates the broadcast layer of the process `process_id`, which holds back at most `capacity`
+  /// broadcasts.
+  pub fn new(process_id: P, capacity: usize) -> Self {
+    Self {
+      vector_clock: VectorClock::new(process_id),
+      held_back: Vec::new(),
+      capacity,
+    }
+  }
+
+  /// Returns how many broadcasts have been delivered from each process.
+  pub fn vector_clock(&self) -> &VectorClock<P, C> {
+    &self.vector_clock
+  }
+
+  /// Returns the broadcasts waiting for their causal predecessors, in arrival order.
+  pub fn held_back(&self) -> &[Broadcast<P, C, T>] {
+    &self.held_back
+  }
+
+  /// Returns how many broadcasts can be held back at once.
+  pub fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  /// Stamps `payload` to be broadcast to every other process. The broadcast counts as delivered
+  /// by this process.
+  ///
+  /// Fails if the counter of this process would overflow.
+  pub fn broadcast(&mut self, payload: T) -> Result<Broadcast<P, C, T>> {
+    self.vector_clock.increment_this_process_clock()?;
+    Ok(Broadcast {
+      vector_clock: self.vector_clock.clone(),
+      payload,
+    })
+  }
+
+  /// Receives `broadcast` and returns the payloads that became deliverable, in causal order: the
+  /// payload of `broadcast` followed by the payloads it was holding back, or nothing if it must be
+  /// held back itself. Broadcasts that were already delivered or are already held back are
+  /// ignored.
+  ///
+  /// Fails if `broadcast` must be held back but the hold-back queue is full, in which case it's
+  /// dropped and must be received again later.
+  pub fn receive(&mut self, broadcast: Broadcast<P, C, T>) -> Result<Vec<T>> {
+    if self.is_delivered(&broadcast) || self.is_held_back(&broadcast) {
+      return Ok(Vec::new());
+    }
+
+    if !self.is_deliverable(&broadcast) {
+      if self.held_back.len() >= self.capacity {
+        return Err(Error::QueueFull(format!(
+          "cannot hold back more than {} broadcasts",
+          self.capacity
+        )));
+      }
+      self.held_back.push(broadcast);
+      return Ok(Vec::new());
+    }
+
+    let mut delivered = vec![self.deliver(broadcast)];
+    while let Some(i) = self
+      .held_back
+      .iter()
+      .position(|broadcast| self.is_deliverable(broadcast))
+    {
+      let broadcast = self.held_back.remove(i);
+      delivered.push(self.deliver(broadcast));
+    }
+
+    Ok(delivered)
+  }
+
+  fn deliver(&mut self, broadcast: Broadcast<P, C, T>) -> T {
+    let sender = broadcast.sender().clone();
+    self
+      .vector_clock
+      .set_clock(&sender, broadcast.vector_clock.clock(&sender));
+    broadcast.payload
+  }
+
+  /// Returns true when the broadcast is the next one from its sender and every broadcast its
+  /// sender had delivered has been delivered.
+  fn is_deliverable(&self, broadcast: &Broadcast<P, C, T>) -> bool {
+    let sender = broadcast.sender();
+    self.vector_clock.clock(sender).checked_increment()
+      == Some(broadcast.vector_clock.clock(sender))
+      && broadcast
+        .vector_clock
+        .clocks()
+        .iter()
+        .all(|(process_id, counter)| {
+          process_id == sender || *counter <= self.vector_clock.clock(process_id)
+        })
+  }
+
+  fn is_delivered(&self, broadcast: &Broadcast<P, C, T>) -> bool {
+    let sender = broadcast.sender();
+    broadcast.vector_clock.clock(sender) <= self.vector_clock.clock(sender)
+  }
+
+  fn is_held_back(&self, broadcast: &Broadcast<P, C, T>) -> bool {
+    let sender = broadcast.sender();
+    self.held_back.iter().any(|held_back| {
+      held_back.sender() == sender
+        && held_back.vector_clock.clock(sender) == broadcast.vector_clock.clock(sender)
+    })
+  }
+}
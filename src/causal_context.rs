@@ -0,0 +1,55 @@
+use crate::{Counter, ProcessId, VectorClock};
+
+/// An opaque token describing the values a client has seen, returned by
+/// [`Process::get`](crate::Process::get) and [`Process::put`](crate::Process::put).
+///
+/// Passing the context of a read to a later write tells the process which values the write
+/// supersedes. Writes from clients that haven't read the key use the default, empty, context.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(bound(deserialize = "P: crate::ProcessId + serde::Deserialize<'de>, \
+    C: crate::Counter + serde::Deserialize<'de>"))
+)]
+pub struct CausalContext<P = usize, C = u64> {
+  /// `None` for the empty context.
+  vector_clock: Option<VectorClock<P, C>>,
+}
+
+impl<P: ProcessId, C: Counter> CausalContext<P, C> {
+  pub(crate) fn new(vector_clock: VectorClock<P, C>) -> Self {
+    Self {
+      vector_clock: Some(vector_clock),
+    }
+  }
+
+  /// Returns the vector clock described by the context, `None` for the empty context.
+  pub(crate) fn vector_clock(&self) -> Option<&VectorClock<P, C>> {
+    self.vector_clock.as_ref()
+  }
+
+  /// Returns the vector clock described by the context, owned by `process_id` when the context is
+  /// empty.
+  pub(crate) fn into_vector_clock(self, process_id: &P) -> VectorClock<P, C> {
+    self
+      .vector_clock
+      .unwrap_or_else(|| VectorClock::new(process_id.clone()))
+  }
+}
+
+impl<P, C> Default for CausalContext<P, C> {
+  fn default() -> Self {
+    Self { vector_clock: None }
+  }
+}
+
+impl<P: ProcessId, C: Counter> PartialEq for CausalContext<P, C> {
+  fn eq(&self, other: &Self) -> bool {
+    match (&self.vector_clock, &other.vector_clock) {
+      (Some(vector_clock), Some(other_vector_clock)) => vector_clock == other_vector_clock,
+      (Some(vector_clock), None) | (None, Some(vector_clock)) => vector_clock.clocks().is_empty(),
+      (None, None) => true,
+    }
+  }
+}
@@ -0,0 +1,35 @@
+/// The causal relationship between two events, as told by their clocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CausalOrdering {
+  /// The first event happened before the second one.
+  Before,
+  /// The first event happened after the second one.
+  After,
+  /// Both clocks describe the same causal history.
+  Equal,
+  /// Neither event knows about the other one.
+  Concurrent,
+}
+
+impl CausalOrdering {
+  /// Returns the ordering seen from the other event's point of view.
+  pub fn reverse(self) -> Self {
+    match self {
+      CausalOrdering::Before => CausalOrdering::After,
+      CausalOrdering::After => CausalOrdering::Before,
+      CausalOrdering::Equal => CausalOrdering::Equal,
+      CausalOrdering::Concurrent => CausalOrdering::Concurrent,
+    }
+  }
+
+  /// Converts the causal ordering into the partial ordering used by [`PartialOrd`].
+  pub fn to_partial_ordering(self) -> Option<std::cmp::Ordering> {
+    match self {
+      CausalOrdering::Before => Some(std::cmp::Ordering::Less),
+      CausalOrdering::After => Some(std::cmp::Ordering::Greater),
+      CausalOrdering::Equal => Some(std::cmp::Ordering::Equal),
+      CausalOrdering::Concurrent => None,
+    }
+  }
+}
@@ -0,0 +1,12 @@
+/// Returns the CRC-32 checksum of `bytes`, with the polynomial used by zlib and Ethernet.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+  let mut crc = !0u32;
+  for byte in bytes {
+    crc ^= u32::from(*byte);
+    for _ in 0..8 {
+      let mask = (crc & 1).wrapping_neg();
+      crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+    }
+  }
+  !crc
+}
@@ -0,0 +1,100 @@
+use crate::{Sibling, VectorClock};
+
+/// What to do with the siblings of a key, as decided by a [`ConflictResolver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+  /// Keep every sibling and let clients resolve the conflict on their next write.
+  KeepAll,
+  /// Keep only the sibling at this index of the siblings given to the resolver. Every sibling is
+  /// kept if the index is out of range.
+  Keep(usize),
+  /// Replace every sibling with this value, written as if by a client that had read all of them.
+  Merge(String),
+}
+
+/// Decides what a [`Process`](crate::Process) does when a write leaves a key with concurrent
+/// values. Resolvers must be deterministic so that every process reaches the same decision for the
+/// same siblings.
+pub trait ConflictResolver<P, C, V = VectorClock<P, C>> {
+  /// Resolves the conflict between `siblings`, the concurrent values and tombstones of `key`. Only
+  /// called when there's more than one sibling.
+  fn resolve(&self, key: &str, siblings: &[Sibling<P, V>]) -> Resolution;
+}
+
+/// Keeps every concurrent value, Dynamo style. Reads return all of them and the next write with
+/// their context collapses them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeepSiblings;
+
+impl<P, C, V> ConflictResolver<P, C, V> for KeepSiblings {
+  fn resolve(&self, _key: &str, _siblings: &[Sibling<P, V>]) -> Resolution {
+    Resolution::KeepAll
+  }
+}
+
+/// Keeps the value or tombstone with the latest timestamp. Ties are broken by process id and then
+/// by value.
+///
+/// Concurrent writes are silently discarded, so this only fits data where losing a concurrent
+/// update is acceptable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LastWriterWins;
+
+impl<P: Ord, C, V> ConflictResolver<P, C, V> for LastWriterWins {
+  fn resolve(&self, _key: &str, siblings: &[Sibling<P, V>]) -> Resolution {
+    keep_max_by(siblings, |a, b| {
+      (a.timestamp, &a.process_id, &a.value).cmp(&(b.timestamp, &b.process_id, &b.value))
+    })
+  }
+}
+
+/// Keeps the value or tombstone written through the process with the greatest process id. Ties are
+/// broken by timestamp and then by value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HighestProcessIdWins;
+
+impl<P: Ord, C, V> ConflictResolver<P, C, V> for HighestProcessIdWins {
+  fn resolve(&self, _key: &str, siblings: &[Sibling<P, V>]) -> Resolution {
+    keep_max_by(siblings, |a, b| {
+      (&a.process_id, a.timestamp, &a.value).cmp(&(&b.process_id, b.timestamp, &b.value))
+    })
+  }
+}
+
+/// Merges concurrent values into a single one with a user supplied function, which receives the
+/// key and its concurrent values. Tombstones are left out of the merge, superseding the deletes
+/// concurrent with the merged values.
+#[derive(Debug, Clone, Copy)]
+pub struct MergeWith<F>(pub F);
+
+impl<P, C, V, F> ConflictResolver<P, C, V> for MergeWith<F>
+where
+  F: Fn(&str, &[&String]) -> String,
+{
+  fn resolve(&self, key: &str, siblings: &[Sibling<P, V>]) -> Resolution {
+    let values: Vec<&String> = siblings
+      .iter()
+      .filter_map(|sibling| sibling.value.as_ref())
+      .collect();
+    if values.is_empty() {
+      return Resolution::KeepAll;
+    }
+
+    Resolution::Merge((self.0)(key, &values))
+  }
+}
+
+/// Keeps the greatest sibling according to `compare`.
+fn keep_max_by<P, V>(
+  siblings: &[Sibling<P, V>],
+  compare: impl Fn(&Sibling<P, V>, &Sibling<P, V>) -> std::cmp::Ordering,
+) -> Resolution {
+  match siblings
+    .iter()
+    .enumerate()
+    .max_by(|(_, a), (_, b)| compare(a, b))
+  {
+    Some((i, _)) => Resolution::Keep(i),
+    None => Resolution::KeepAll,
+  }
+}
@@ -0,0 +1,38 @@
+use std::{fmt::Debug, hash::Hash};
+
+/// The integer type used for the entries of a clock.
+///
+/// Increments are checked: a counter that would wrap around is reported as
+/// [`Error::CounterOverflow`](crate::Error::CounterOverflow) instead, since a wrapped counter would
+/// make later events look like they happened before earlier ones.
+pub trait Counter: Copy + Ord + Hash + Debug + Default {
+  /// The largest value the counter can hold.
+  const MAX: Self;
+
+  /// Returns the counter plus one, or `None` if it would overflow.
+  fn checked_increment(self) -> Option<Self>;
+}
+
+impl Counter for u8 {
+  const MAX: Self = u8::MAX;
+
+  fn checked_increment(self) -> Option<Self> {
+    self.checked_add(1)
+  }
+}
+
+impl Counter for u32 {
+  const MAX: Self = u32::MAX;
+
+  fn checked_increment(self) -> Option<Self> {
+    self.checked_add(1)
+  }
+}
+
+impl Counter for u64 {
+  const MAX: Self = u64::MAX;
+
+  fn checked_increment(self) -> Option<Self> {
+    self.checked_add(1)
+  }
+}
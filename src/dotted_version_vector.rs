@@ -0,0 +1,179 @@
+use crate::{CausalOrdering, Counter, ProcessId, Result, VectorClock, Version};
+
+/// A single event: the `counter`-th event of the process `process_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dot<P = usize, C = u64> {
+  pub process_id: P,
+  pub counter: C,
+}
+
+/// A [dotted version vector][DottedVersionVectors] describes the causal history of a write as the
+/// vector clock the writer had seen plus the single event, the dot, of the write itself.
+///
+/// Unlike a vector clock, the dot doesn't imply the events that precede it on the same process,
+/// so two writes coordinated by the same process for writers that hadn't seen each other's writes
+/// are correctly told apart as concurrent. Used as the [`Version`] of a
+/// [`KvEntry`](crate::KvEntry), it keeps siblings from being discarded or piling up because of
+/// false dominance.
+///
+/// [DottedVersionVectors]: https://arxiv.org/abs/1011.5808
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(bound(deserialize = "P: crate::ProcessId + serde::Deserialize<'de>, \
+    C: crate::Counter + serde::Deserialize<'de>"))
+)]
+pub struct DottedVersionVector<P = usize, C = u64> {
+  /// `None` for a version vector that only describes a context, like the join of two versions.
+  dot: Option<Dot<P, C>>,
+  /// The events seen by the writer.
+  context: VectorClock<P, C>,
+}
+
+impl<P: ProcessId, C: Counter> DottedVersionVector<P, C> {
+  /// Creates the version of the write `dot` from a writer that had seen `context`.
+  pub fn new(dot: Dot<P, C>, context: VectorClock<P, C>) -> Self {
+    Self {
+      dot: Some(dot),
+      context,
+    }
+  }
+
+  /// Creates a version vector without a dot that describes the events of `context`.
+  pub fn from_context(context: VectorClock<P, C>) -> Self {
+    Self { dot: None, context }
+  }
+
+  /// Returns the event of the write, if any.
+  pub fn dot(&self) -> Option<&Dot<P, C>> {
+    self.dot.as_ref()
+  }
+
+  /// Returns the events seen by the writer.
+  pub fn context(&self) -> &VectorClock<P, C> {
+    &self.context
+  }
+
+  /// Returns true when the event `counter` of `process_id` is part of the causal history.
+  pub fn contains(&self, process_id: &P, counter: C) -> bool {
+    counter <= self.context.clock(process_id)
+      || self
+        .dot
+        .as_ref()
+        .is_some_and(|dot| &dot.process_id == process_id && dot.counter == counter)
+  }
+
+  /// Compares the causal histories described by `self` and `other`.
+  pub fn compare(&self, other: &DottedVersionVector<P, C>) -> CausalOrdering {
+    match (self.is_included_in(other), other.is_included_in(self)) {
+      (true, true) => CausalOrdering::Equal,
+      (true, false) => CausalOrdering::Before,
+      (false, true) => CausalOrdering::After,
+      (false, false) => CausalOrdering::Concurrent,
+    }
+  }
+
+  /// Returns true when the write described by `self` happened before the one described by `other`.
+  pub fn happened_before(&self, other: &DottedVersionVector<P, C>) -> bool {
+    self.compare(other) == CausalOrdering::Before
+  }
+
+  /// Returns true when `self` has seen every event `other` has seen.
+  pub fn dominates(&self, other: &DottedVersionVector<P, C>) -> bool {
+    other.is_included_in(self)
+  }
+
+  /// Returns true when neither version has seen the write of the other one.
+  pub fn is_concurrent_with(&self, other: &DottedVersionVector<P, C>) -> bool {
+    self.compare(other) == CausalOrdering::Concurrent
+  }
+
+  /// Returns a version vector without a dot that covers the histories of both versions.
+  pub fn join(&self, other: &DottedVersionVector<P, C>) -> DottedVersionVector<P, C> {
+    let mut version = self.clone();
+    version.join_in_place(other);
+    version
+  }
+
+  /// Same as [`DottedVersionVector::join`] but updates `self` instead of returning a new version.
+  pub fn join_in_place(&mut self, other: &DottedVersionVector<P, C>) {
+    self.context = self.to_vector_clock();
+    self.context.join_in_place(&other.to_vector_clock());
+    self.dot = None;
+  }
+
+  /// Returns true when every event of `self` is part of the history of `other`.
+  fn is_included_in(&self, other: &DottedVersionVector<P, C>) -> bool {
+    if let Some(dot) = &self.dot {
+      if !other.contains(&dot.process_id, dot.counter) {
+        return false;
+      }
+    }
+
+    self.context.clocks().iter().all(|(process_id, counter)| {
+      let other_counter = other.context.clock(process_id);
+      // The dot of `other` extends its context when it's the next event of the process.
+      *counter <= other_counter
+        || other.dot.as_ref().is_some_and(|dot| {
+          &dot.process_id == process_id
+            && other_counter.checked_increment() == Some(dot.counter)
+            && *counter <= dot.counter
+        })
+    })
+  }
+}
+
+impl<P: ProcessId, C: Counter> PartialEq for DottedVersionVector<P, C> {
+  fn eq(&self, other: &Self) -> bool {
+    self.compare(other) == CausalOrdering::Equal
+  }
+}
+
+impl<P: ProcessId, C: Counter> PartialOrd for DottedVersionVector<P, C> {
+  fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    self.compare(other).to_partial_ordering()
+  }
+}
+
+impl<P: ProcessId, C: Counter> Version<P, C> for DottedVersionVector<P, C> {
+  fn for_write(process_id: &P, counter: C, context: &VectorClock<P, C>) -> Result<Self> {
+    Ok(DottedVersionVector::new(
+      Dot {
+        process_id: process_id.clone(),
+        counter,
+      },
+      context.clone(),
+    ))
+  }
+
+  fn compare(&self, other: &Self) -> CausalOrdering {
+    DottedVersionVector::compare(self, other)
+  }
+
+  fn is_covered_by(&self, context: &VectorClock<P, C>) -> bool {
+    self
+      .dot
+      .as_ref()
+      .is_none_or(|dot| dot.counter <= context.clock(&dot.process_id))
+      && context.dominates(&self.context)
+  }
+
+  fn to_vector_clock(&self) -> VectorClock<P, C> {
+    let mut vector_clock = self.context.clone();
+    if let Some(dot) = &self.dot {
+      if dot.counter > vector_clock.clock(&dot.process_id) {
+        vector_clock.set_clock(&dot.process_id, dot.counter);
+      }
+    }
+    vector_clock
+  }
+
+  fn retain_processes(&mut self, mut keep: impl FnMut(&P) -> bool) {
+    if self.dot.as_ref().is_some_and(|dot| !keep(&dot.process_id)) {
+      self.dot = None;
+    }
+    self.context.retain_processes(keep);
+  }
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use crate::{CausalOrdering, Counter, Error, ProcessId, Result, VectorClock};
+
+/// A [dotted version vector set][DottedVersionVectorSets] stores the concurrent values of a key
+/// together with their causal history in a single compact clock, instead of one clock per value.
+///
+/// For each process, the set stores the counter of the latest write it coordinated and the
+/// values of its writes that haven't been superseded yet, newest first. Each value is the write
+/// identified by the dot made of the process and the counter stored next to it, and its history
+/// is everything the set has seen, so a write supersedes exactly the values its context covers no
+/// matter how many clients write through the same process.
+///
+/// [DottedVersionVectorSets]: https://arxiv.org/abs/1011.5808
+#[derive(Debug, Clone)]
+pub struct DottedVersionVectorSet<P = usize, C = u64, T = String> {
+  /// The id of the process that owns the set and coordinates its writes.
+  process_id: P,
+  /// The latest counter seen for each process and its values that are still live.
+  entries: BTreeMap<P, (C, Vec<(C, T)>)>,
+}
+
+impl<P: ProcessId, C: Counter, T: Clone> DottedVersionVectorSet<P, C, T> {
+  /// Creates an empty set owned by `process_id`.
+  pub fn new(process_id: P) -> Self {
+    Self {
+      process_id,
+      entries: BTreeMap::new(),
+    }
+  }
+
+  /// Returns the id of the process that owns this set.
+  pub fn process_id(&self) -> &P {
+    &self.process_id
+  }
+
+  /// Returns every live value, grouped by the process that coordinated its write.
+  pub fn values(&self) -> impl Iterator<Item = &T> {
+    self
+      .entries
+      .values()
+      .flat_map(|(_, values)| values.iter().map(|(_, value)| value))
+  }
+
+  /// Returns how many values are live.
+  pub fn len(&self) -> usize {
+    self.entries.values().map(|(_, values)| values.len()).sum()
+  }
+
+  /// Returns true when there are no live values.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the causal context of the set: a vector clock covering every write it has seen. A
+  /// write carrying this context supersedes every live value.
+  pub fn join(&self) -> VectorClock<P, C> {
+    let mut vector_clock = VectorClock::new(self.process_id.clone());
+    for (process_id, (counter, _)) in self.entries.iter() {
+      vector_clock.set_clock(process_id, *counter);
+    }
+    vector_clock
+  }
+
+  /// Records a write of `value` coordinated by the process that owns the set, from a writer that
+  /// had seen `context`. Values covered by `context` are superseded, the others are kept.
+  ///
+  /// Fails if the counter of the process that owns the set would overflow, in which case the set
+  /// is left untouched.
+  pub fn update(&mut self, context: &VectorClock<P, C>, value: T) -> Result<()> {
+    let counter = self
+      .entries
+      .get(&self.process_id)
+      .map(|(counter, _)| *counter)
+      .unwrap_or_default()
+      .checked_increment()
+      .ok_or_else(|| Error::CounterOverflow(format!("{:?}", self.process_id)))?;
+
+    for (process_id, (_, values)) in self.entries.iter_mut() {
+      let seen = context.clock(process_id);
+      values.retain(|(value_counter, _)| *value_counter > seen);
+    }
+    for (process_id, seen) in context.clocks().iter() {
+      let (counter, _) = self
+        .entries
+        .entry(process_id.clone())
+        .or_insert_with(|| (C::default(), Vec::new()));
+      *counter = std::cmp::max(*counter, *seen);
+    }
+
+    let (latest, values) = self
+      .entries
+      .entry(self.process_id.clone())
+      .or_insert_with(|| (C::default(), Vec::new()));
+    *latest = counter;
+    values.insert(0, (counter, value));
+
+    Ok(())
+  }
+
+  /// Merges `other` into `self`, usually the copy of the same key on another process. A value is
+  /// kept when both sets still have it or when the set that doesn't have it never saw its write.
+  pub fn sync(&mut self, other: &DottedVersionVectorSet<P, C, T>) {
+    for (process_id, (other_counter, other_values)) in other.entries.iter() {
+      let (counter, values) = self
+        .entries
+        .entry(process_id.clone())
+        .or_insert_with(|| (C::default(), Vec::new()));
+
+      let mut merged: Vec<(C, T)> = values
+        .iter()
+        .filter(|(value_counter, _)| {
+          value_counter > other_counter
+            || other_values
+              .iter()
+              .any(|(other_value_counter, _)| other_value_counter == value_counter)
+        })
+        .cloned()
+        .collect();
+      merged.extend(
+        other_values
+          .iter()
+          .filter(|(other_value_counter, _)| other_value_counter > counter)
+          .cloned(),
+      );
+      merged.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+      *counter = std::cmp::max(*counter, *other_counter);
+      *values = merged;
+    }
+  }
+
+  /// Compares the causal histories described by `self` and `other`.
+  pub fn compare(&self, other: &DottedVersionVectorSet<P, C, T>) -> CausalOrdering {
+    self.join().compare(&other.join())
+  }
+}
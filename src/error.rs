@@ -0,0 +1,82 @@
+/// Errors returned by the operations of this crate.
+///
+/// Process ids are generic, so errors that refer to a process carry its [`Debug`] representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+  /// The process is not a member of the system.
+  UnknownProcess(String),
+  /// The process is already a member of the system.
+  DuplicateProcess(String),
+  /// The id belonged to a process that has been retired and cannot be used again.
+  RetiredProcess(String),
+  /// The process id cannot be used for the requested operation, like a process retiring itself.
+  InvalidProcessId(String),
+  /// There's no entry for the key.
+  UnknownKey(String),
+  /// Incrementing the logical clock of the process would overflow its counter.
+  CounterOverflow(String),
+  /// The remote timestamp is further ahead of the local physical clock than the maximum drift
+  /// allowed.
+  ClockDrift(String),
+  /// The configuration is not valid, like a Bloom clock without cells or clocks of different
+  /// sizes.
+  InvalidConfiguration(String),
+  /// An I/O operation failed, like reading from a socket or writing to a file.
+  Io(String),
+  /// The queue is full and cannot hold any more items.
+  QueueFull(String),
+  /// The interval tree clock cannot be used for the requested operation, like recording an event
+  /// on an anonymous stamp or joining stamps whose ids overlap.
+  InvalidStamp(String),
+  /// The bytes are not a valid encoding.
+  InvalidEncoding(String),
+  /// The process hasn't seen every value of the key seen by the session, so reading from it would
+  /// break the guarantees of the session.
+  NotCaughtUp(String),
+}
+
+impl std::fmt::Display for Error {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Error::UnknownProcess(process_id) => {
+        write!(f, "process {process_id} is not a member of the system")
+      }
+      Error::DuplicateProcess(process_id) => {
+        write!(f, "process {process_id} is already a member of the system")
+      }
+      Error::RetiredProcess(process_id) => {
+        write!(f, "process {process_id} has been retired")
+      }
+      Error::InvalidProcessId(process_id) => {
+        write!(f, "process id {process_id} is not valid for this operation")
+      }
+      Error::UnknownKey(key) => write!(f, "key {key:?} not found"),
+      Error::CounterOverflow(process_id) => {
+        write!(f, "the logical clock of process {process_id} overflowed")
+      }
+      Error::ClockDrift(reason) => write!(f, "clock drift too large: {reason}"),
+      Error::InvalidConfiguration(reason) => write!(f, "invalid configuration: {reason}"),
+      Error::Io(reason) => write!(f, "i/o error: {reason}"),
+      Error::QueueFull(reason) => write!(f, "queue full: {reason}"),
+      Error::InvalidStamp(reason) => write!(f, "invalid interval tree clock: {reason}"),
+      Error::InvalidEncoding(reason) => write!(f, "invalid encoding: {reason}"),
+      Error::NotCaughtUp(key) => {
+        write!(
+          f,
+          "the process has not caught up with the session on key {key:?}"
+        )
+      }
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+  fn from(error: std::io::Error) -> Self {
+    Error::Io(error.to_string())
+  }
+}
+
+/// Shorthand for results whose error is [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,132 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Error, Result};
+
+/// A source of wall clock time, in milliseconds since the Unix epoch.
+///
+/// Implemented by [`SystemClock`] and by any `Fn() -> u64`, so tests can drive a
+/// [`HybridLogicalClock`] with a clock they control.
+pub trait PhysicalClock {
+  /// Returns the current time in milliseconds since the Unix epoch.
+  fn now(&self) -> u64;
+}
+
+/// The wall clock of the machine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl PhysicalClock for SystemClock {
+  fn now(&self) -> u64 {
+    SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_millis() as u64)
+      .unwrap_or(0)
+  }
+}
+
+impl<F: Fn() -> u64> PhysicalClock for F {
+  fn now(&self) -> u64 {
+    self()
+  }
+}
+
+/// A timestamp of a [`HybridLogicalClock`]. Timestamps are totally ordered by physical time and
+/// then by logical counter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HybridTimestamp {
+  /// The largest physical time seen, in milliseconds since the Unix epoch.
+  pub physical: u64,
+  /// Counts the events that happened while the physical time didn't move forward.
+  pub logical: u32,
+}
+
+/// A [hybrid logical clock][HybridLogicalClocks] produces timestamps that stay close to wall clock
+/// time while respecting causality: if an event happened before another one, its timestamp is
+/// smaller.
+///
+/// Unlike a [`VectorClock`](crate::VectorClock), a timestamp can't tell concurrent events apart,
+/// but it has a constant size and can be used where wall clock time is expected, like TTLs or last
+/// writer wins tie-breaking.
+///
+/// Remote timestamps further ahead than the maximum drift are rejected, so that a process with a
+/// broken clock can't drag every other process into the future.
+///
+/// [HybridLogicalClocks]: https://cse.buffalo.edu/tech-reports/2014-04.pdf
+#[derive(Debug, Clone)]
+pub struct HybridLogicalClock<T = SystemClock> {
+  physical_clock: T,
+  /// How far ahead of the physical clock a remote timestamp can be, in milliseconds.
+  max_drift: u64,
+  /// The timestamp of the latest event.
+  latest: HybridTimestamp,
+}
+
+impl<T: PhysicalClock> HybridLogicalClock<T> {
+  /// Creates a clock driven by `physical_clock` that rejects remote timestamps more than
+  /// `max_drift` milliseconds ahead of it.
+  pub fn new(physical_clock: T, max_drift: u64) -> Self {
+    Self {
+      physical_clock,
+      max_drift,
+      latest: HybridTimestamp::default(),
+    }
+  }
+
+  /// Returns the timestamp of the latest event.
+  pub fn latest(&self) -> HybridTimestamp {
+    self.latest
+  }
+
+  /// Records a local event and returns its timestamp.
+  ///
+  /// Fails if the logical counter would overflow, which only happens when the physical clock
+  /// doesn't move forward for `u32::MAX` events. The clock is left untouched.
+  pub fn local_event(&mut self) -> Result<HybridTimestamp> {
+    let physical = std::cmp::max(self.latest.physical, self.physical_clock.now());
+    self.advance(physical, self.latest)
+  }
+
+  /// Records the sending of a message and returns the timestamp to attach to it.
+  ///
+  /// Fails like [`HybridLogicalClock::local_event`].
+  pub fn send(&mut self) -> Result<HybridTimestamp> {
+    self.local_event()
+  }
+
+  /// Records the receipt of a message sent with `remote` and returns the timestamp of the
+  /// receipt, which is greater than both `remote` and every earlier local timestamp.
+  ///
+  /// Fails if `remote` is more than the maximum drift ahead of the physical clock or if the
+  /// logical counter would overflow, in which case the clock is left untouched.
+  pub fn receive(&mut self, remote: &HybridTimestamp) -> Result<HybridTimestamp> {
+    let now = self.physical_clock.now();
+    if remote.physical > now.saturating_add(self.max_drift) {
+      return Err(Error::ClockDrift(format!(
+        "remote timestamp {} is {}ms ahead of the physical clock",
+        remote.physical,
+        remote.physical - now
+      )));
+    }
+
+    let physical = std::cmp::max(now, std::cmp::max(self.latest.physical, remote.physical));
+    let latest = std::cmp::max(self.latest, *remote);
+    self.advance(physical, latest)
+  }
+
+  /// Moves the clock to `physical`. The logical counter continues from `latest` when the physical
+  /// time is the same as its physical time, and starts over otherwise.
+  fn advance(&mut self, physical: u64, latest: HybridTimestamp) -> Result<HybridTimestamp> {
+    let logical = if physical == latest.physical {
+      latest
+        .logical
+        .checked_add(1)
+        .ok_or_else(|| Error::CounterOverflow("hybrid logical clock".to_owned()))?
+    } else {
+      0
+    };
+
+    self.latest = HybridTimestamp { physical, logical };
+    Ok(self.latest)
+  }
+}
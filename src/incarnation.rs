@@ -0,0 +1,33 @@
+use crate::ProcessId;
+
+/// A process id paired with an epoch, used to let long-lived processes roll their counters over.
+///
+/// When the counter of a process is about to overflow, the process starts a new epoch with
+/// [`Process::roll_over`](crate::Process::roll_over): it joins the system again as a new
+/// incarnation whose counter starts from zero. Events of the previous incarnation keep their
+/// entries in the clocks, so causality is preserved, and the previous incarnation can be retired
+/// once its writes have reached every other process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Incarnation<P> {
+  pub process_id: P,
+  pub epoch: u64,
+}
+
+impl<P: ProcessId> Incarnation<P> {
+  /// Returns the first incarnation of `process_id`.
+  pub fn first(process_id: P) -> Self {
+    Self {
+      process_id,
+      epoch: 0,
+    }
+  }
+
+  /// Returns the incarnation that follows this one.
+  pub fn next(&self) -> Self {
+    Self {
+      process_id: self.process_id.clone(),
+      epoch: self.epoch + 1,
+    }
+  }
+}
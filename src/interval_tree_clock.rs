@@ -0,0 +1,634 @@
+use crate::{CausalOrdering, Error, Result};
+
+/// Extra cost of growing an event tree by expanding one of its leaves, so that growing where the
+/// tree is already deep is preferred.
+const EXPAND_COST: u64 = 1 << 32;
+
+/// An [interval tree clock][IntervalTreeClocks] tracks causality between replicas that are created
+/// and retired dynamically, without a global way of naming them.
+///
+/// Each stamp owns a portion of the interval `[0, 1)`, its id, and records its events over that
+/// portion. New replicas are created by forking a stamp, which splits its id in two, and retired
+/// by joining stamps back together, so the size of a stamp follows the number of live replicas
+/// instead of every replica that ever existed. Trees are kept normalised, in their smallest form.
+///
+/// Stamps are partially ordered like vector clocks: two stamps are equal when they describe the
+/// same causal history, regardless of their ids.
+///
+/// [IntervalTreeClocks]: https://gsd.di.uminho.pt/members/cbm/ps/itc2008.pdf
+#[derive(Debug, Clone)]
+pub struct IntervalTreeClock {
+  /// The portion of the interval owned by the stamp.
+  id: Id,
+  /// The events seen by the stamp over the whole interval.
+  event: Event,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Id {
+  /// The whole subinterval is owned, or none of it is.
+  Leaf(bool),
+  Node(Box<Id>, Box<Id>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Event {
+  Leaf(u64),
+  /// The events over the whole subinterval plus the events over each half of it.
+  Node(u64, Box<Event>, Box<Event>),
+}
+
+impl IntervalTreeClock {
+  /// Creates the first stamp of a system, which owns the whole interval and hasn't seen any
+  /// events yet.
+  pub fn seed() -> Self {
+    Self {
+      id: Id::Leaf(true),
+      event: Event::Leaf(0),
+    }
+  }
+
+  /// Returns true when the stamp doesn't own any portion of the interval. Anonymous stamps can't
+  /// record events, they are only used to carry causal histories, like in messages.
+  pub fn is_anonymous(&self) -> bool {
+    self.id == Id::Leaf(false)
+  }
+
+  /// Splits the stamp into two stamps that have seen the same events and own half of its id each.
+  pub fn fork(self) -> (Self, Self) {
+    let (left, right) = self.id.split();
+    (
+      Self {
+        id: left,
+        event: self.event.clone(),
+      },
+      Self {
+        id: right,
+        event: self.event,
+      },
+    )
+  }
+
+  /// Merges two stamps into one that owns both ids and has seen the events of both, usually to
+  /// retire a replica.
+  ///
+  /// Fails if the ids of both stamps overlap, which only happens when a stamp is joined with a
+  /// stamp that wasn't forked from it or with a copy of itself.
+  pub fn join(self, other: Self) -> Result<Self> {
+    Ok(Self {
+      id: self.id.sum(&other.id)?,
+      event: self.event.join(&other.event),
+    })
+  }
+
+  /// Returns an anonymous copy of the stamp, which has seen the same events without owning any
+  /// portion of the interval.
+  pub fn peek(&self) -> Self {
+    Self {
+      id: Id::Leaf(false),
+      event: self.event.clone(),
+    }
+  }
+
+  /// Records an event that happened in the replica that owns this stamp.
+  ///
+  /// Fails if the stamp is anonymous or if a counter of the event tree would overflow, in which
+  /// case the stamp is left untouched.
+  pub fn event(&mut self) -> Result<()> {
+    if self.is_anonymous() {
+      return Err(Error::InvalidStamp(
+        "anonymous stamps cannot record events".to_owned(),
+      ));
+    }
+
+    let filled = self.event.fill(&self.id);
+    if filled != self.event {
+      self.event = filled;
+    } else {
+      let (grown, _) = self.event.grow(&self.id)?;
+      self.event = grown;
+    }
+
+    Ok(())
+  }
+
+  /// Records the receipt of a message sent with `other`, usually a stamp returned by
+  /// [`IntervalTreeClock::peek`]. Only the events of `other` are merged, its id is ignored.
+  ///
+  /// Fails if the stamp is anonymous or if a counter of the event tree would overflow, in which
+  /// case the stamp is left untouched.
+  pub fn message_received(&mut self, other: &IntervalTreeClock) -> Result<()> {
+    let mut stamp = Self {
+      id: self.id.clone(),
+      event: self.event.join(&other.event),
+    };
+    stamp.event()?;
+    *self = stamp;
+    Ok(())
+  }
+
+  /// Returns true when `other` has seen every event `self` has seen.
+  pub fn leq(&self, other: &IntervalTreeClock) -> bool {
+    self.event.leq(0, &other.event, 0)
+  }
+
+  /// Compares the causal histories described by `self` and `other`.
+  pub fn compare(&self, other: &IntervalTreeClock) -> CausalOrdering {
+    match (self.leq(other), other.leq(self)) {
+      (true, true) => CausalOrdering::Equal,
+      (true, false) => CausalOrdering::Before,
+      (false, true) => CausalOrdering::After,
+      (false, false) => CausalOrdering::Concurrent,
+    }
+  }
+
+  /// Encodes the stamp with the compact bit level encoding of the interval tree clocks paper:
+  /// small trees and counters take a few bits each. The last byte is padded with zeros.
+  pub fn encode(&self) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    self.id.encode(&mut writer);
+    self.event.encode(&mut writer);
+    writer.bytes
+  }
+
+  /// Decodes a stamp encoded with [`IntervalTreeClock::encode`].
+  ///
+  /// Fails if `bytes` is not a valid encoding.
+  pub fn decode(bytes: &[u8]) -> Result<Self> {
+    let mut reader = BitReader { bytes, position: 0 };
+    let id = Id::decode(&mut reader, 0)?;
+    let event = Event::decode(&mut reader, 0)?;
+
+    let remaining = bytes.len() * 8 - reader.position;
+    if remaining >= 8 || reader.read(remaining as u32)? != 0 {
+      return Err(Error::InvalidEncoding(
+        "trailing bits after the stamp".to_owned(),
+      ));
+    }
+
+    Ok(Self { id, event })
+  }
+}
+
+impl PartialEq for IntervalTreeClock {
+  fn eq(&self, other: &Self) -> bool {
+    self.compare(other) == CausalOrdering::Equal
+  }
+}
+
+impl PartialOrd for IntervalTreeClock {
+  fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    self.compare(other).to_partial_ordering()
+  }
+}
+
+impl Id {
+  /// Creates a node, normalised: a node whose halves are both owned or both not owned is a leaf.
+  fn node(left: Id, right: Id) -> Id {
+    match (left, right) {
+      (Id::Leaf(left), Id::Leaf(right)) if left == right => Id::Leaf(left),
+      (left, right) => Id::Node(Box::new(left), Box::new(right)),
+    }
+  }
+
+  /// Splits the id in two disjoint ids whose sum is the id.
+  fn split(&self) -> (Id, Id) {
+    match self {
+      Id::Leaf(false) => (Id::Leaf(false), Id::Leaf(false)),
+      Id::Leaf(true) => (
+        Id::node(Id::Leaf(true), Id::Leaf(false)),
+        Id::node(Id::Leaf(false), Id::Leaf(true)),
+      ),
+      Id::Node(left, right) if **left == Id::Leaf(false) => {
+        let (first, second) = right.split();
+        (
+          Id::node(Id::Leaf(false), first),
+          Id::node(Id::Leaf(false), second),
+        )
+      }
+      Id::Node(left, right) if **right == Id::Leaf(false) => {
+        let (first, second) = left.split();
+        (
+          Id::node(first, Id::Leaf(false)),
+          Id::node(second, Id::Leaf(false)),
+        )
+      }
+      Id::Node(left, right) => (
+        Id::node((**left).clone(), Id::Leaf(false)),
+        Id::node(Id::Leaf(false), (**right).clone()),
+      ),
+    }
+  }
+
+  /// Returns the id that owns the portions of both ids.
+  fn sum(&self, other: &Id) -> Result<Id> {
+    match (self, other) {
+      (Id::Leaf(false), id) | (id, Id::Leaf(false)) => Ok(id.clone()),
+      (Id::Node(left, right), Id::Node(other_left, other_right)) => {
+        Ok(Id::node(left.sum(other_left)?, right.sum(other_right)?))
+      }
+      _ => Err(Error::InvalidStamp(
+        "the ids of the stamps overlap".to_owned(),
+      )),
+    }
+  }
+
+  fn encode(&self, writer: &mut BitWriter) {
+    match self {
+      Id::Leaf(owned) => {
+        writer.write(0b00, 2);
+        writer.write(*owned as u64, 1);
+      }
+      Id::Node(left, right) if **left == Id::Leaf(false) => {
+        writer.write(0b01, 2);
+        right.encode(writer);
+      }
+      Id::Node(left, right) if **right == Id::Leaf(false) => {
+        writer.write(0b10, 2);
+        left.encode(writer);
+      }
+      Id::Node(left, right) => {
+        writer.write(0b11, 2);
+        left.encode(writer);
+        right.encode(writer);
+      }
+    }
+  }
+
+  /// Decodes an id found `depth` nodes below the root.
+  fn decode(reader: &mut BitReader<'_>, depth: usize) -> Result<Id> {
+    check_depth(depth)?;
+    match reader.read(2)? {
+      0b00 => Ok(Id::Leaf(reader.read(1)? == 1)),
+      0b01 => Ok(Id::node(Id::Leaf(false), Id::decode(reader, depth + 1)?)),
+      0b10 => Ok(Id::node(Id::decode(reader, depth + 1)?, Id::Leaf(false))),
+      _ => {
+        let left = Id::decode(reader, depth + 1)?;
+        let right = Id::decode(reader, depth + 1)?;
+        Ok(Id::node(left, right))
+      }
+    }
+  }
+}
+
+impl Event {
+  /// Creates a node, normalised: a node with two equal leaves is a leaf and the smallest value of
+  /// the children is moved up to the node.
+  fn node(value: u64, left: Event, right: Event) -> Event {
+    match (left, right) {
+      (Event::Leaf(left), Event::Leaf(right)) if left == right => Event::Leaf(value + left),
+      (left, right) => {
+        let min = std::cmp::min(left.min(), right.min());
+        Event::Node(
+          value + min,
+          Box::new(left.sink(min)),
+          Box::new(right.sink(min)),
+        )
+      }
+    }
+  }
+
+  /// Returns the value of the root of the tree.
+  fn value(&self) -> u64 {
+    match self {
+      Event::Leaf(value) | Event::Node(value, _, _) => *value,
+    }
+  }
+
+  /// Returns the tree with `amount` added to its root.
+  fn lift(&self, amount: u64) -> Event {
+    match self {
+      Event::Leaf(value) => Event::Leaf(value + amount),
+      Event::Node(value, left, right) => Event::Node(value + amount, left.clone(), right.clone()),
+    }
+  }
+
+  /// Returns the tree with `amount` subtracted from its root.
+  fn sink(self, amount: u64) -> Event {
+    match self {
+      Event::Leaf(value) => Event::Leaf(value - amount),
+      Event::Node(value, left, right) => Event::Node(value - amount, left, right),
+    }
+  }
+
+  /// Returns the smallest number of events seen over any portion of the interval.
+  fn min(&self) -> u64 {
+    match self {
+      Event::Leaf(value) => *value,
+      Event::Node(value, left, right) => value + std::cmp::min(left.min(), right.min()),
+    }
+  }
+
+  /// Returns the largest number of events seen over any portion of the interval.
+  fn max(&self) -> u64 {
+    match self {
+      Event::Leaf(value) => *value,
+      Event::Node(value, left, right) => value + std::cmp::max(left.max(), right.max()),
+    }
+  }
+
+  /// Returns the smallest tree that has seen the events of both trees.
+  fn join(&self, other: &Event) -> Event {
+    match (self, other) {
+      (Event::Leaf(value), Event::Leaf(other_value)) => {
+        Event::Leaf(std::cmp::max(*value, *other_value))
+      }
+      (Event::Leaf(value), Event::Node(..)) => {
+        Event::Node(*value, Box::new(Event::Leaf(0)), Box::new(Event::Leaf(0))).join(other)
+      }
+      (Event::Node(..), Event::Leaf(other_value)) => self.join(&Event::Node(
+        *other_value,
+        Box::new(Event::Leaf(0)),
+        Box::new(Event::Leaf(0)),
+      )),
+      (Event::Node(value, ..), Event::Node(other_value, ..)) if value > other_value => {
+        other.join(self)
+      }
+      (Event::Node(value, left, right), Event::Node(other_value, other_left, other_right)) => {
+        let difference = other_value - value;
+        Event::node(
+          *value,
+          left.join(&other_left.lift(difference)),
+          right.join(&other_right.lift(difference)),
+        )
+      }
+    }
+  }
+
+  /// Returns true when `other` lifted by `other_base` has seen every event `self` lifted by
+  /// `base` has seen.
+  fn leq(&self, base: u64, other: &Event, other_base: u64) -> bool {
+    match self {
+      Event::Leaf(value) => value + base <= other.value() + other_base,
+      Event::Node(value, left, right) => {
+        let value = value + base;
+        if value > other.value() + other_base {
+          return false;
+        }
+
+        match other {
+          Event::Leaf(_) => {
+            left.leq(value, other, other_base) && right.leq(value, other, other_base)
+          }
+          Event::Node(other_value, other_left, other_right) => {
+            let other_value = other_value + other_base;
+            left.leq(value, other_left, other_value) && right.leq(value, other_right, other_value)
+          }
+        }
+      }
+    }
+  }
+
+  /// Raises the tree over the portions owned by `id` as much as possible without adding events
+  /// that weren't seen before: every event seen over a neighbouring portion is now seen over the
+  /// portion owned by `id`. Returns the tree unchanged when it can't be raised.
+  fn fill(&self, id: &Id) -> Event {
+    match (id, self) {
+      (Id::Leaf(false), _) => self.clone(),
+      (Id::Leaf(true), _) => Event::Leaf(self.max()),
+      (Id::Node(..), Event::Leaf(_)) => self.clone(),
+      (Id::Node(id_left, id_right), Event::Node(value, left, right)) => {
+        if **id_left == Id::Leaf(true) {
+          let right = right.fill(id_right);
+          let left = std::cmp::max(left.max(), right.min());
+          Event::node(*value, Event::Leaf(left), right)
+        } else if **id_right == Id::Leaf(true) {
+          let left = left.fill(id_left);
+          let right = std::cmp::max(right.max(), left.min());
+          Event::node(*value, left, Event::Leaf(right))
+        } else {
+          Event::node(*value, left.fill(id_left), right.fill(id_right))
+        }
+      }
+    }
+  }
+
+  /// Records a new event over a portion owned by `id`, picking the portion that keeps the tree
+  /// the smallest. Returns the new tree together with the cost of the change.
+  fn grow(&self, id: &Id) -> Result<(Event, u64)> {
+    match (id, self) {
+      (Id::Leaf(false), _) => Err(Error::InvalidStamp(
+        "anonymous stamps cannot record events".to_owned(),
+      )),
+      (Id::Leaf(true), _) => {
+        let value = self
+          .max()
+          .checked_add(1)
+          .ok_or_else(|| Error::CounterOverflow("interval tree clock".to_owned()))?;
+        Ok((Event::Leaf(value), 0))
+      }
+      (Id::Node(..), Event::Leaf(value)) => {
+        let (event, cost) =
+          Event::Node(*value, Box::new(Event::Leaf(0)), Box::new(Event::Leaf(0))).grow(id)?;
+        Ok((event, cost.saturating_add(EXPAND_COST)))
+      }
+      (Id::Node(id_left, id_right), Event::Node(value, left, right)) => {
+        let grow_left = || -> Result<(Event, u64)> {
+          let (left, cost) = left.grow(id_left)?;
+          Ok((Event::node(*value, left, (**right).clone()), cost))
+        };
+        let grow_right = || -> Result<(Event, u64)> {
+          let (right, cost) = right.grow(id_right)?;
+          Ok((Event::node(*value, (**left).clone(), right), cost))
+        };
+
+        let (event, cost) = if **id_left == Id::Leaf(false) {
+          grow_right()?
+        } else if **id_right == Id::Leaf(false) {
+          grow_left()?
+        } else {
+          let (left_event, left_cost) = grow_left()?;
+          let (right_event, right_cost) = grow_right()?;
+          if left_cost < right_cost {
+            (left_event, left_cost)
+          } else {
+            (right_event, right_cost)
+          }
+        };
+        Ok((event, cost.saturating_add(1)))
+      }
+    }
+  }
+
+  fn encode(&self, writer: &mut BitWriter) {
+    match self {
+      Event::Leaf(value) => {
+        writer.write(0b1, 1);
+        encode_number(*value, writer);
+      }
+      Event::Node(value, left, right) => {
+        writer.write(0b0, 1);
+        let is_zero = |event: &Event| *event == Event::Leaf(0);
+        match (*value, is_zero(left), is_zero(right)) {
+          (0, true, _) => {
+            writer.write(0b00, 2);
+            right.encode(writer);
+          }
+          (0, _, true) => {
+            writer.write(0b01, 2);
+            left.encode(writer);
+          }
+          (0, _, _) => {
+            writer.write(0b10, 2);
+            left.encode(writer);
+            right.encode(writer);
+          }
+          (value, true, _) => {
+            writer.write(0b11, 2);
+            writer.write(0b00, 2);
+            encode_number(value, writer);
+            right.encode(writer);
+          }
+          (value, _, true) => {
+            writer.write(0b11, 2);
+            writer.write(0b01, 2);
+            encode_number(value, writer);
+            left.encode(writer);
+          }
+          (value, _, _) => {
+            writer.write(0b11, 2);
+            writer.write(0b1, 1);
+            encode_number(value, writer);
+            left.encode(writer);
+            right.encode(writer);
+          }
+        }
+      }
+    }
+  }
+
+  /// Decodes an event tree found `depth` nodes below the root.
+  fn decode(reader: &mut BitReader<'_>, depth: usize) -> Result<Event> {
+    check_depth(depth)?;
+    if reader.read(1)? == 1 {
+      return Ok(Event::Leaf(decode_number(reader)?));
+    }
+
+    let (value, left, right) = match reader.read(2)? {
+      0b00 => (0, Event::Leaf(0), Event::decode(reader, depth + 1)?),
+      0b01 => (0, Event::decode(reader, depth + 1)?, Event::Leaf(0)),
+      0b10 => (
+        0,
+        Event::decode(reader, depth + 1)?,
+        Event::decode(reader, depth + 1)?,
+      ),
+      _ => {
+        if reader.read(1)? == 1 {
+          (
+            decode_number(reader)?,
+            Event::decode(reader, depth + 1)?,
+            Event::decode(reader, depth + 1)?,
+          )
+        } else if reader.read(1)? == 0 {
+          (
+            decode_number(reader)?,
+            Event::Leaf(0),
+            Event::decode(reader, depth + 1)?,
+          )
+        } else {
+          (
+            decode_number(reader)?,
+            Event::decode(reader, depth + 1)?,
+            Event::Leaf(0),
+          )
+        }
+      }
+    };
+
+    let max = std::cmp::max(left.max(), right.max());
+    if value.checked_add(max).is_none() {
+      return Err(Error::InvalidEncoding(
+        "event counter out of range".to_owned(),
+      ));
+    }
+    Ok(Event::node(value, left, right))
+  }
+}
+
+/// The depth of the deepest tree that's decoded. An id that deep would own `2^-128` of the
+/// interval, so deeper trees only come from corrupted input, which would otherwise overflow the
+/// stack of the decoder.
+const MAX_DEPTH: usize = 128;
+
+/// Fails if a node found `depth` nodes below the root is deeper than [`MAX_DEPTH`].
+fn check_depth(depth: usize) -> Result<()> {
+  if depth > MAX_DEPTH {
+    return Err(Error::InvalidEncoding(format!(
+      "tree deeper than {MAX_DEPTH} nodes"
+    )));
+  }
+  Ok(())
+}
+
+/// Encodes `value` with as few bits as possible for small numbers: numbers below `2^2` take 3
+/// bits, the next `2^3` numbers take 5 bits, and so on.
+fn encode_number(value: u64, writer: &mut BitWriter) {
+  let mut value = value;
+  let mut bits = 2;
+  while bits < 64 && value >= 1 << bits {
+    writer.write(0b1, 1);
+    value -= 1 << bits;
+    bits += 1;
+  }
+  writer.write(0b0, 1);
+  writer.write(value, bits);
+}
+
+fn decode_number(reader: &mut BitReader<'_>) -> Result<u64> {
+  let mut offset: u64 = 0;
+  let mut bits = 2;
+  while reader.read(1)? == 1 {
+    if bits == 64 {
+      return Err(Error::InvalidEncoding("number out of range".to_owned()));
+    }
+    offset += 1 << bits;
+    bits += 1;
+  }
+  offset
+    .checked_add(reader.read(bits)?)
+    .ok_or_else(|| Error::InvalidEncoding("number out of range".to_owned()))
+}
+
+/// Writes bits most significant first.
+#[derive(Default)]
+struct BitWriter {
+  bytes: Vec<u8>,
+  /// How many bits have been written.
+  position: usize,
+}
+
+impl BitWriter {
+  /// Writes the `bits` lowest bits of `value`.
+  fn write(&mut self, value: u64, bits: u32) {
+    for i in (0..bits).rev() {
+      if self.position.is_multiple_of(8) {
+        self.bytes.push(0);
+      }
+      let bit = ((value >> i) & 1) as u8;
+      *self.bytes.last_mut().unwrap() |= bit << (7 - self.position % 8);
+      self.position += 1;
+    }
+  }
+}
+
+struct BitReader<'a> {
+  bytes: &'a [u8],
+  /// How many bits have been read.
+  position: usize,
+}
+
+impl BitReader<'_> {
+  /// Reads `bits` bits into the lowest bits of a number.
+  fn read(&mut self, bits: u32) -> Result<u64> {
+    let mut value = 0;
+    for _ in 0..bits {
+      let byte = self
+        .bytes
+        .get(self.position / 8)
+        .ok_or_else(|| Error::InvalidEncoding("unexpected end of input".to_owned()))?;
+      value = (value << 1) | ((byte >> (7 - self.position % 8)) & 1) as u64;
+      self.position += 1;
+    }
+    Ok(value)
+  }
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+
+use crate::{
+  CausalOrdering, ConflictResolver, Counter, ProcessId, Resolution, VectorClock, Version,
+};
+
+/// A value written to a key together with the version of the write.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sibling<P = usize, V = VectorClock<P>> {
+  /// The causal history of the write.
+  pub version: V,
+  /// `None` for a tombstone left by a delete.
+  pub value: Option<String>,
+  /// The process that coordinated the write.
+  pub process_id: P,
+  /// Wall clock time of the write on the process that coordinated it, in milliseconds since the
+  /// Unix epoch.
+  pub timestamp: u64,
+}
+
+impl<P, V> Sibling<P, V> {
+  /// Returns true when the sibling was written by a delete.
+  pub fn is_tombstone(&self) -> bool {
+    self.value.is_none()
+  }
+}
+
+impl<P: PartialEq, V: PartialEq> PartialEq for Sibling<P, V> {
+  fn eq(&self, other: &Self) -> bool {
+    self.version == other.version
+      && self.value == other.value
+      && self.process_id == other.process_id
+      && self.timestamp == other.timestamp
+  }
+}
+
+/// The values stored for a key.
+///
+/// Writes are never overwritten by writes that haven't seen them: an entry keeps every value
+/// whose write wasn't covered by the causal context of a later write. Values written concurrently
+/// are kept side by side as siblings until a write whose context covers all of them collapses
+/// them into a single value.
+///
+/// Deletes are writes too: they leave a tombstone sibling so that older values can't be brought
+/// back by processes that haven't seen the delete. Tombstones are purged once every process has
+/// acknowledged them.
+///
+/// The version of each value is a [`VectorClock`] by default. A
+/// [`DottedVersionVector`](crate::DottedVersionVector) tells apart concurrent writes coordinated
+/// by the same process, which vector clocks can mistake for ordered ones.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(
+    try_from = "KvEntryFields<P, C, V>",
+    bound(deserialize = "P: ProcessId + serde::Deserialize<'de>, \
+      C: Counter + serde::Deserialize<'de>, V: Version<P, C> + serde::Deserialize<'de>")
+  )
+)]
+pub struct KvEntry<P = usize, C = u64, V = VectorClock<P, C>> {
+  /// Never empty: entries are only stored by a process after their first write and are removed
+  /// once their last tombstone is purged.
+  siblings: Vec<Sibling<P, V>>,
+  /// The latest context of the key acknowledged by each of the other processes.
+  acknowledgments: BTreeMap<P, VectorClock<P, C>>,
+}
+
+/// The fields of a [`KvEntry`] as deserialized, before checking that it has siblings.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "P: ProcessId + serde::Deserialize<'de>, \
+  C: Counter + serde::Deserialize<'de>, V: serde::Deserialize<'de>"))]
+struct KvEntryFields<P, C, V> {
+  siblings: Vec<Sibling<P, V>>,
+  acknowledgments: BTreeMap<P, VectorClock<P, C>>,
+}
+
+#[cfg(feature = "serde")]
+impl<P: ProcessId, C: Counter, V: Version<P, C>> TryFrom<KvEntryFields<P, C, V>>
+  for KvEntry<P, C, V>
+{
+  type Error = crate::Error;
+
+  fn try_from(fields: KvEntryFields<P, C, V>) -> crate::Result<Self> {
+    if fields.siblings.is_empty() {
+      return Err(crate::Error::InvalidEncoding(
+        "entry without siblings".to_owned(),
+      ));
+    }
+    Ok(Self::from_parts(fields.siblings, fields.acknowledgments))
+  }
+}
+
+impl<P: ProcessId, C: Counter, V: Version<P, C>> KvEntry<P, C, V> {
+  /// Creates the entry of a key that hasn't been written to yet.
+  pub(crate) fn new() -> Self {
+    Self {
+      siblings: Vec::new(),
+      acknowledgments: BTreeMap::new(),
+    }
+  }
+
+  /// Creates an entry from its siblings and acknowledgments, as decoded from the wire.
+  pub(crate) fn from_parts(
+    siblings: Vec<Sibling<P, V>>,
+    acknowledgments: BTreeMap<P, VectorClock<P, C>>,
+  ) -> Self {
+    Self {
+      siblings,
+      acknowledgments,
+    }
+  }
+
+  /// Returns every value and tombstone stored for the key together with the version of its write.
+  pub fn siblings(&self) -> &[Sibling<P, V>] {
+    &self.siblings
+  }
+
+  /// Returns the latest context of the key acknowledged by each of the other processes.
+  pub(crate) fn acknowledgments(&self) -> &BTreeMap<P, VectorClock<P, C>> {
+    &self.acknowledgments
+  }
+
+  /// Returns every value stored for the key, leaving out tombstones.
+  pub fn values(&self) -> impl Iterator<Item = &String> {
+    self
+      .siblings
+      .iter()
+      .filter_map(|sibling| sibling.value.as_ref())
+  }
+
+  /// Returns true when the key has more than one value or tombstone because of concurrent writes.
+  pub fn has_siblings(&self) -> bool {
+    self.siblings.len() > 1
+  }
+
+  /// Returns true when every sibling is a tombstone.
+  pub fn is_deleted(&self) -> bool {
+    self.siblings.iter().all(Sibling::is_tombstone)
+  }
+
+  /// Returns the causal context of the entry: the join of the versions of every sibling. A write
+  /// carrying this context supersedes every value currently stored.
+  pub fn context(&self) -> VectorClock<P, C> {
+    let mut context = VectorClock::new(self.siblings[0].process_id.clone());
+    for sibling in self.siblings.iter() {
+      context.join_in_place(&sibling.version.to_vector_clock());
+    }
+    context
+  }
+
+  /// Records a write of `value`, or a delete when `value` is `None`, coordinated by `process_id`
+  /// at `timestamp` from a writer that had seen `context`. `version` is the version of the write.
+  ///
+  /// Siblings covered by `context` are superseded by the write, the others are kept.
+  pub(crate) fn write(
+    &mut self,
+    process_id: &P,
+    version: V,
+    context: &VectorClock<P, C>,
+    value: Option<String>,
+    timestamp: u64,
+  ) {
+    self
+      .siblings
+      .retain(|sibling| !sibling.version.is_covered_by(context));
+    self.siblings.push(Sibling {
+      version,
+      value,
+      process_id: process_id.clone(),
+      timestamp,
+    });
+  }
+
+  /// Merges the siblings of `other`, usually the copy of the same key on another process. Siblings
+  /// whose write happened before the write of another sibling are superseded.
+  pub(crate) fn merge(&mut self, other: &KvEntry<P, C, V>) {
+    let mut siblings: Vec<Sibling<P, V>> = Vec::new();
+    for sibling in self.siblings.iter().chain(other.siblings.iter()) {
+      let is_superseded = self
+        .siblings
+        .iter()
+        .chain(other.siblings.iter())
+        .any(|other_sibling| {
+          sibling.version.compare(&other_sibling.version) == CausalOrdering::Before
+        });
+      let is_duplicate = siblings
+        .iter()
+        .any(|kept| kept.version.compare(&sibling.version) == CausalOrdering::Equal);
+      if !is_superseded && !is_duplicate {
+        siblings.push(sibling.clone());
+      }
+    }
+    self.siblings = siblings;
+  }
+
+  /// Lets `resolver` decide what to do with the siblings of the entry, if there are any. Returns
+  /// the merged value to write with the context of the entry when the resolver merges them.
+  pub(crate) fn resolve<R: ConflictResolver<P, C, V>>(
+    &mut self,
+    key: &str,
+    resolver: &R,
+  ) -> Option<String> {
+    if !self.has_siblings() {
+      return None;
+    }
+
+    match resolver.resolve(key, &self.siblings) {
+      Resolution::KeepAll => None,
+      Resolution::Keep(i) if i < self.siblings.len() => {
+        let sibling = self.siblings.swap_remove(i);
+        self.siblings = vec![sibling];
+        None
+      }
+      Resolution::Keep(_) => None,
+      Resolution::Merge(value) => Some(value),
+    }
+  }
+
+  /// Records that `process_id` has seen the key up to `vector_clock`.
+  pub(crate) fn acknowledge(&mut self, process_id: &P, vector_clock: &VectorClock<P, C>) {
+    match self.acknowledgments.get_mut(process_id) {
+      Some(acknowledged) => acknowledged.join_in_place(vector_clock),
+      None => {
+        self
+          .acknowledgments
+          .insert(process_id.clone(), vector_clock.clone());
+      }
+    }
+  }
+
+  /// Purges the tombstones that every process in `processes` has acknowledged and returns how many
+  /// were purged. The entry may be left without siblings, in which case it should be removed.
+  pub(crate) fn collect_garbage<'a>(&mut self, processes: impl Iterator<Item = &'a P>) -> usize
+  where
+    P: 'a,
+  {
+    // The largest clock every process has acknowledged, `None` when there are no other processes.
+    let mut stable: Option<VectorClock<P, C>> = None;
+    for process_id in processes {
+      let Some(acknowledged) = self.acknowledgments.get(process_id) else {
+        return 0;
+      };
+      match stable.as_mut() {
+        Some(stable) => stable.meet_in_place(acknowledged),
+        None => stable = Some(acknowledged.clone()),
+      }
+    }
+
+    self.purge_tombstones(stable.as_ref())
+  }
+
+  /// Purges the tombstones covered by `stable`, every tombstone when `stable` is `None`, and
+  /// returns how many were purged.
+  pub(crate) fn purge_tombstones(&mut self, stable: Option<&VectorClock<P, C>>) -> usize {
+    let is_stable =
+      |sibling: &Sibling<P, V>| stable.is_none_or(|stable| sibling.version.is_covered_by(stable));
+
+    let siblings = self.siblings.len();
+    self
+      .siblings
+      .retain(|sibling| !(sibling.is_tombstone() && is_stable(sibling)));
+    siblings - self.siblings.len()
+  }
+
+  /// Returns true when the entry has no siblings left.
+  pub(crate) fn is_empty(&self) -> bool {
+    self.siblings.is_empty()
+  }
+
+  /// Forgets every event of the processes for which `keep` returns false, as well as their
+  /// acknowledgments.
+  pub(crate) fn retain_processes(&mut self, mut keep: impl FnMut(&P) -> bool) {
+    for sibling in self.siblings.iter_mut() {
+      sibling.version.retain_processes(&mut keep);
+    }
+
+    self
+      .acknowledgments
+      .retain(|process_id, _| keep(process_id));
+    for acknowledged in self.acknowledgments.values_mut() {
+      acknowledged.retain_processes(&mut keep);
+    }
+  }
+}
+
+impl<P: ProcessId, C: Counter, V: Version<P, C>> PartialEq for KvEntry<P, C, V> {
+  fn eq(&self, other: &Self) -> bool {
+    self.siblings == other.siblings
+  }
+}
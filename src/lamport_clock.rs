@@ -0,0 +1,160 @@
+use crate::{
+  CausalOrdering, Counter, Error, LogicalClock, ProcessId, Result, VectorClock, Version,
+};
+
+/// A [Lamport clock][WikipediaLamportClock] is a single counter per process that orders events
+/// consistently with causality: if an event happened before another one, its clock is smaller.
+///
+/// [WikipediaLamportClock]: https://en.wikipedia.org/wiki/Lamport_timestamp
+///
+/// Clocks are totally ordered by counter and then by process id, so they never report concurrent
+/// events: a smaller clock doesn't mean its event happened before. In exchange, a clock has a
+/// constant size no matter how many processes are part of the system.
+///
+/// Used as the [`Version`] of the values of a [`Process`](crate::Process), concurrent values
+/// replicated from other processes are resolved in favour of the greatest clock. A write is
+/// versioned after every clock its writer had seen, so once a value gets a clock whose counter is
+/// the largest a counter can hold, it can never be superseded: writes that had seen it fail with
+/// [`Error::CounterOverflow`] and writes that hadn't are kept as its siblings. Only the key of that
+/// value is affected, and [rolling over](crate::Process::roll_over) doesn't help since the counter
+/// belongs to the value rather than to a process.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LamportClock<P = usize, C = u64> {
+  /// The id of the process that owns the clock.
+  process_id: P,
+  counter: C,
+}
+
+impl<P: ProcessId, C: Counter> LamportClock<P, C> {
+  /// Creates a clock owned by `process_id` that hasn't seen any events yet.
+  pub fn new(process_id: P) -> Self {
+    Self {
+      process_id,
+      counter: C::default(),
+    }
+  }
+
+  /// Creates a clock owned by `process_id` whose counter is `counter`, as decoded from the wire.
+  pub(crate) fn from_parts(process_id: P, counter: C) -> Self {
+    Self {
+      process_id,
+      counter,
+    }
+  }
+
+  /// Returns the id of the process that owns this clock.
+  pub fn process_id(&self) -> &P {
+    &self.process_id
+  }
+
+  /// Returns the counter of the clock.
+  pub fn counter(&self) -> C {
+    self.counter
+  }
+
+  /// Records an event that happened in the process that owns this clock.
+  ///
+  /// Fails if the counter would overflow.
+  pub fn tick(&mut self) -> Result<()> {
+    self.counter = self.next_counter(self.counter)?;
+    Ok(())
+  }
+
+  /// Records the receipt of a message sent with `other_process_lamport_clock`.
+  ///
+  /// Fails if the counter would overflow, in which case the clock is left untouched.
+  pub fn receive(&mut self, other_process_lamport_clock: &LamportClock<P, C>) -> Result<()> {
+    self.counter = self.next_counter(std::cmp::max(
+      self.counter,
+      other_process_lamport_clock.counter,
+    ))?;
+    Ok(())
+  }
+
+  /// Compares `self` and `other` by counter and then by process id.
+  pub fn compare(&self, other: &LamportClock<P, C>) -> CausalOrdering {
+    match (self.counter, &self.process_id).cmp(&(other.counter, &other.process_id)) {
+      std::cmp::Ordering::Less => CausalOrdering::Before,
+      std::cmp::Ordering::Greater => CausalOrdering::After,
+      std::cmp::Ordering::Equal => CausalOrdering::Equal,
+    }
+  }
+
+  fn next_counter(&self, counter: C) -> Result<C> {
+    counter
+      .checked_increment()
+      .ok_or_else(|| Error::CounterOverflow(format!("{:?}", self.process_id)))
+  }
+}
+
+impl<P: ProcessId, C: Counter> PartialEq for LamportClock<P, C> {
+  fn eq(&self, other: &Self) -> bool {
+    self.compare(other) == CausalOrdering::Equal
+  }
+}
+
+impl<P: ProcessId, C: Counter> Eq for LamportClock<P, C> {}
+
+impl<P: ProcessId, C: Counter> PartialOrd for LamportClock<P, C> {
+  fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<P: ProcessId, C: Counter> Ord for LamportClock<P, C> {
+  fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+    (self.counter, &self.process_id).cmp(&(other.counter, &other.process_id))
+  }
+}
+
+impl<P: ProcessId, C: Counter> LogicalClock for LamportClock<P, C> {
+  fn tick(&mut self) -> Result<()> {
+    LamportClock::tick(self)
+  }
+
+  fn receive(&mut self, other: &Self) -> Result<()> {
+    LamportClock::receive(self, other)
+  }
+
+  fn compare(&self, other: &Self) -> CausalOrdering {
+    LamportClock::compare(self, other)
+  }
+}
+
+impl<P: ProcessId, C: Counter> Version<P, C> for LamportClock<P, C> {
+  fn for_write(process_id: &P, counter: C, context: &VectorClock<P, C>) -> Result<Self> {
+    // The write must be ordered after every write the writer had seen.
+    let seen = context.clocks().values().max().copied().unwrap_or_default();
+    let after_seen = seen
+      .checked_increment()
+      .ok_or_else(|| Error::CounterOverflow(format!("{process_id:?}")))?;
+    Ok(Self {
+      process_id: process_id.clone(),
+      counter: std::cmp::max(counter, after_seen),
+    })
+  }
+
+  fn compare(&self, other: &Self) -> CausalOrdering {
+    LamportClock::compare(self, other)
+  }
+
+  fn is_covered_by(&self, context: &VectorClock<P, C>) -> bool {
+    // Counters of a process only grow, so seeing a later write of the process means having seen
+    // this one.
+    context.clock(&self.process_id) >= self.counter
+  }
+
+  fn to_vector_clock(&self) -> VectorClock<P, C> {
+    let mut vector_clock = VectorClock::new(self.process_id.clone());
+    vector_clock.set_clock(&self.process_id, self.counter);
+    vector_clock
+  }
+
+  fn retain_processes(&mut self, mut keep: impl FnMut(&P) -> bool) {
+    // Like the entries of a vector clock, the writes of a retired process are treated as seen.
+    if !keep(&self.process_id) {
+      self.counter = C::default();
+    }
+  }
+}
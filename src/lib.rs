@@ -0,0 +1,67 @@
+//! Building blocks for tracking causality between processes of a distributed system with
+//! [vector clocks][WikipediaVectorClock], plus a small replicated key-value store built on top of
+//! them.
+//!
+//! [WikipediaVectorClock]: https://en.wikipedia.org/wiki/Vector_clock
+mod bloom_clock;
+mod causal_broadcast;
+mod causal_context;
+mod causal_ordering;
+mod checksum;
+mod conflict_resolver;
+mod counter;
+mod dotted_version_vector;
+mod dotted_version_vector_set;
+mod error;
+mod hybrid_logical_clock;
+mod incarnation;
+mod interval_tree_clock;
+mod kv_entry;
+mod lamport_clock;
+mod logical_clock;
+mod matrix_clock;
+mod membership;
+mod message;
+mod process;
+mod process_id;
+mod protocol;
+mod rng;
+mod session;
+mod simulator;
+mod snapshot;
+mod vector_clock;
+mod version;
+mod wire_format;
+mod write_ahead_log;
+
+pub use bloom_clock::{BloomClock, BloomComparison};
+pub use causal_broadcast::{Broadcast, CausalBroadcast};
+pub use causal_context::CausalContext;
+pub use causal_ordering::CausalOrdering;
+pub use conflict_resolver::{
+  ConflictResolver, HighestProcessIdWins, KeepSiblings, LastWriterWins, MergeWith, Resolution,
+};
+pub use counter::Counter;
+pub use dotted_version_vector::{Dot, DottedVersionVector};
+pub use dotted_version_vector_set::DottedVersionVectorSet;
+pub use error::{Error, Result};
+pub use hybrid_logical_clock::{HybridLogicalClock, HybridTimestamp, PhysicalClock, SystemClock};
+pub use incarnation::Incarnation;
+pub use interval_tree_clock::IntervalTreeClock;
+pub use kv_entry::{KvEntry, Sibling};
+pub use lamport_clock::LamportClock;
+pub use logical_clock::LogicalClock;
+pub use matrix_clock::MatrixClock;
+pub use membership::Membership;
+pub use message::Message;
+pub use process::{GetResult, Process};
+pub use process_id::ProcessId;
+pub use protocol::{read_frame, write_frame, Client, Request, Response, MAX_FRAME_LEN};
+pub use rng::Rng;
+pub use session::Session;
+pub use simulator::{NetworkConfig, SimulatedClock, SimulatedProcess, Simulator};
+pub use snapshot::Snapshot;
+pub use vector_clock::VectorClock;
+pub use version::Version;
+pub use wire_format::{WireFormat, WIRE_FORMAT_VERSION};
+pub use write_ahead_log::{CompactionPolicy, FsyncPolicy, Log, LogRecord, Volatile, WriteAheadLog};
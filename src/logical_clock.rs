@@ -0,0 +1,52 @@
+use std::fmt::Debug;
+
+use crate::{CausalOrdering, Counter, ProcessId, Result, VectorClock};
+
+/// The operations shared by the logical clocks of this crate, so that code that only needs to
+/// timestamp events and messages can pick the clock that fits: a [`VectorClock`] to detect
+/// concurrent events, or a [`LamportClock`](crate::LamportClock) when a total order consistent
+/// with causality is enough.
+///
+/// The trait only covers timestamping events and messages. The types that must tell concurrent
+/// events apart or find missing predecessors are built on [`VectorClock`] and are not generic over
+/// it: [`Message`](crate::Message), [`CausalContext`](crate::CausalContext),
+/// [`CausalBroadcast`](crate::CausalBroadcast) and the clock of a [`Process`](crate::Process). A
+/// process picks a cheaper clock for the versions of its values through its
+/// [`Version`](crate::Version) parameter instead, which [`LamportClock`](crate::LamportClock)
+/// implements.
+pub trait LogicalClock: Clone + Debug {
+  /// Records a local event.
+  ///
+  /// Fails if the counter of the clock would overflow, in which case the clock is left untouched.
+  fn tick(&mut self) -> Result<()>;
+
+  /// Records the sending of a message and returns the clock to attach to it.
+  ///
+  /// Fails like [`LogicalClock::tick`].
+  fn send(&mut self) -> Result<Self> {
+    self.tick()?;
+    Ok(self.clone())
+  }
+
+  /// Records the receipt of a message sent with `other`.
+  ///
+  /// Fails if the counter of the clock would overflow, in which case the clock is left untouched.
+  fn receive(&mut self, other: &Self) -> Result<()>;
+
+  /// Compares the events described by `self` and `other`.
+  fn compare(&self, other: &Self) -> CausalOrdering;
+}
+
+impl<P: ProcessId, C: Counter> LogicalClock for VectorClock<P, C> {
+  fn tick(&mut self) -> Result<()> {
+    self.increment_this_process_clock()
+  }
+
+  fn receive(&mut self, other: &Self) -> Result<()> {
+    self.message_received(other)
+  }
+
+  fn compare(&self, other: &Self) -> CausalOrdering {
+    VectorClock::compare(self, other)
+  }
+}
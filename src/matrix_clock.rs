@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use crate::{CausalOrdering, Counter, LogicalClock, ProcessId, Result, VectorClock};
+
+/// A [matrix clock][WikipediaMatrixClock] is a vector clock plus what the process that owns it
+/// knows about the vector clock of every other process.
+///
+/// [WikipediaMatrixClock]: https://en.wikipedia.org/wiki/Matrix_clock
+///
+/// Messages carry the whole matrix, so processes learn what their peers have seen either directly
+/// or through other processes. The [stable](MatrixClock::stable) clock, the meet of the views of
+/// every process, describes the events every process is known to have seen, which is what's needed
+/// to safely discard tombstones and old versions.
+#[derive(Debug, Clone)]
+pub struct MatrixClock<P = usize, C = u64> {
+  /// The id of the process that owns the clock.
+  process_id: P,
+  /// The latest known vector clock of each process, including the one that owns the clock.
+  /// Processes without a row haven't been heard from yet.
+  rows: BTreeMap<P, VectorClock<P, C>>,
+}
+
+impl<P: ProcessId, C: Counter> MatrixClock<P, C> {
+  /// Creates a clock owned by `process_id` that hasn't seen any events yet.
+  pub fn new(process_id: P) -> Self {
+    let mut rows = BTreeMap::new();
+    rows.insert(process_id.clone(), VectorClock::new(process_id.clone()));
+    Self { process_id, rows }
+  }
+
+  /// Returns the id of the process that owns this clock.
+  pub fn process_id(&self) -> &P {
+    &self.process_id
+  }
+
+  /// Returns the vector clock of the process that owns this clock.
+  pub fn vector_clock(&self) -> &VectorClock<P, C> {
+    &self.rows[&self.process_id]
+  }
+
+  /// Returns the latest known vector clock of `process_id`, if it has been heard from.
+  pub fn view(&self, process_id: &P) -> Option<&VectorClock<P, C>> {
+    self.rows.get(process_id)
+  }
+
+  /// Records an event that happened in the process that owns this clock.
+  ///
+  /// Fails if the logical clock of the process would overflow.
+  pub fn tick(&mut self) -> Result<()> {
+    self
+      .rows
+      .get_mut(&self.process_id)
+      .expect("the row of the process that owns the clock always exists")
+      .increment_this_process_clock()
+  }
+
+  /// Records that the process that owns this clock has seen the events of `vector_clock`, like
+  /// the [`Process::vector_clock`](crate::Process::vector_clock) of its process. Seeing events
+  /// doesn't count as an event.
+  pub fn observe(&mut self, vector_clock: &VectorClock<P, C>) {
+    self
+      .rows
+      .get_mut(&self.process_id)
+      .expect("the row of the process that owns the clock always exists")
+      .join_in_place(vector_clock);
+  }
+
+  /// Records the receipt of a message sent with `other_process_matrix_clock`: the vector clock of
+  /// this process records the receipt, and every row learns what the sender knew.
+  ///
+  /// Fails if the logical clock of the process that owns this clock would overflow, in which case
+  /// the clock is left untouched.
+  pub fn receive(&mut self, other_process_matrix_clock: &MatrixClock<P, C>) -> Result<()> {
+    let mut vector_clock = self.vector_clock().clone();
+    vector_clock.message_received(other_process_matrix_clock.vector_clock())?;
+
+    for (process_id, other_row) in other_process_matrix_clock.rows.iter() {
+      self
+        .rows
+        .entry(process_id.clone())
+        .or_insert_with(|| VectorClock::new(process_id.clone()))
+        .join_in_place(other_row);
+    }
+    self.rows.insert(self.process_id.clone(), vector_clock);
+
+    Ok(())
+  }
+
+  /// Returns the events the process that owns this clock and every process in `processes` are
+  /// known to have seen: the meet of their rows. Processes that haven't been heard from are known
+  /// to have seen nothing.
+  pub fn stable<'a>(&self, processes: impl IntoIterator<Item = &'a P>) -> VectorClock<P, C>
+  where
+    P: 'a,
+  {
+    let mut stable = self.vector_clock().clone();
+    for process_id in processes {
+      match self.rows.get(process_id) {
+        Some(row) => stable.meet_in_place(row),
+        None => return VectorClock::new(self.process_id.clone()),
+      }
+    }
+    stable
+  }
+
+  /// Forgets every event and row of the processes for which `keep` returns false. Used to compact
+  /// clocks once processes have been retired from the system.
+  pub fn retain_processes(&mut self, mut keep: impl FnMut(&P) -> bool) {
+    let process_id = self.process_id.clone();
+    self
+      .rows
+      .retain(|row_process_id, _| row_process_id == &process_id || keep(row_process_id));
+    for row in self.rows.values_mut() {
+      row.retain_processes(&mut keep);
+    }
+  }
+}
+
+impl<P: ProcessId, C: Counter> LogicalClock for MatrixClock<P, C> {
+  fn tick(&mut self) -> Result<()> {
+    MatrixClock::tick(self)
+  }
+
+  fn receive(&mut self, other: &Self) -> Result<()> {
+    MatrixClock::receive(self, other)
+  }
+
+  /// Compares the vector clocks of the processes that own both clocks.
+  fn compare(&self, other: &Self) -> CausalOrdering {
+    self.vector_clock().compare(other.vector_clock())
+  }
+}
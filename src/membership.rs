@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+
+use crate::{Error, ProcessId, Result};
+
+/// The processes that are part of the system.
+///
+/// Every change to the membership starts a new epoch. The view records the epoch in which each
+/// process joined and the epoch in which each departed process was retired, so that the id of a
+/// retired process is never handed to a new process: clocks may still carry the events of the
+/// retired process and a new process reusing its id would be mistaken for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Membership<P = usize> {
+  /// Incremented every time a process joins or is retired.
+  epoch: u64,
+  /// The epoch in which each current member joined.
+  members: BTreeMap<P, u64>,
+  /// The epoch in which each departed process was retired.
+  retired: BTreeMap<P, u64>,
+}
+
+impl<P: ProcessId> Membership<P> {
+  /// Creates the membership of a system made of `processes`, all of them joining in epoch zero.
+  pub fn new(processes: impl IntoIterator<Item = P>) -> Self {
+    Self {
+      epoch: 0,
+      members: processes
+        .into_iter()
+        .map(|process_id| (process_id, 0))
+        .collect(),
+      retired: BTreeMap::new(),
+    }
+  }
+
+  /// Returns the current epoch.
+  pub fn epoch(&self) -> u64 {
+    self.epoch
+  }
+
+  /// Returns the ids of the current members.
+  pub fn members(&self) -> impl Iterator<Item = &P> {
+    self.members.keys()
+  }
+
+  /// Returns true when `process_id` is a current member.
+  pub fn is_member(&self, process_id: &P) -> bool {
+    self.members.contains_key(process_id)
+  }
+
+  /// Returns true when `process_id` belonged to a process that has been retired.
+  pub fn is_retired(&self, process_id: &P) -> bool {
+    self.retired.contains_key(process_id)
+  }
+
+  /// Returns the epoch in which `process_id` joined, if it's a current member.
+  pub fn joined_in(&self, process_id: &P) -> Option<u64> {
+    self.members.get(process_id).copied()
+  }
+
+  /// Returns the epoch in which `process_id` was retired, if it has been retired.
+  pub fn retired_in(&self, process_id: &P) -> Option<u64> {
+    self.retired.get(process_id).copied()
+  }
+
+  /// Checks that `process_id` can be added to the system, without adding it.
+  ///
+  /// Fails if `process_id` is already a member or belonged to a retired process.
+  pub fn can_add(&self, process_id: &P) -> Result<()> {
+    if self.is_retired(process_id) {
+      return Err(Error::RetiredProcess(format!("{process_id:?}")));
+    }
+    if self.is_member(process_id) {
+      return Err(Error::DuplicateProcess(format!("{process_id:?}")));
+    }
+    Ok(())
+  }
+
+  /// Adds `process_id` to the system and returns the epoch in which it joined.
+  ///
+  /// Fails like [`Membership::can_add`].
+  pub fn add(&mut self, process_id: P) -> Result<u64> {
+    self.can_add(&process_id)?;
+
+    self.epoch += 1;
+    self.members.insert(process_id, self.epoch);
+    Ok(self.epoch)
+  }
+
+  /// Removes `process_id` from the system for good and returns the epoch in which it was retired.
+  ///
+  /// Fails if `process_id` is not a member.
+  pub fn retire(&mut self, process_id: &P) -> Result<u64> {
+    if self.members.remove(process_id).is_none() {
+      return Err(Error::UnknownProcess(format!("{process_id:?}")));
+    }
+
+    self.epoch += 1;
+    self.retired.insert(process_id.clone(), self.epoch);
+    Ok(self.epoch)
+  }
+}
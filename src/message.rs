@@ -0,0 +1,18 @@
+use crate::VectorClock;
+
+/// A write of `value` to `key`, or a delete of `key` when `value` is `None`, sent to a
+/// [`Process`](crate::Process).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(bound(deserialize = "P: crate::ProcessId + serde::Deserialize<'de>, \
+    C: crate::Counter + serde::Deserialize<'de>"))
+)]
+pub struct Message<P = usize, C = u64> {
+  /// The causal context of the writer: the join of the vector clocks of every value it had seen
+  /// before writing, usually obtained from [`KvEntry::context`](crate::KvEntry::context).
+  pub vector_clock: VectorClock<P, C>,
+  pub key: String,
+  pub value: Option<String>,
+}
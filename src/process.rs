@@ -0,0 +1,654 @@
+use std::collections::HashMap;
+
+use crate::{
+  CausalContext, ConflictResolver, Counter, Error, Incarnation, KeepSiblings, KvEntry, Log,
+  LogRecord, Membership, Message, PhysicalClock, ProcessId, Result, Session, Snapshot, SystemClock,
+  VectorClock, Version, Volatile,
+};
+
+/// The values stored for a key, as returned to clients by [`Process::get`].
+#[derive(Debug, Clone)]
+pub struct GetResult<P = usize, C = u64> {
+  /// Every value stored for the key. More than one value means the key was written concurrently.
+  pub values: Vec<String>,
+  /// The context to pass to [`Process::put`] to supersede every value in `values`.
+  pub context: CausalContext<P, C>,
+}
+
+/// A process that's part of the system. Each process holds its own copy of the key-value store.
+///
+/// Concurrent writes to a key are handed to the [`ConflictResolver`] of the process, which keeps
+/// them as siblings by default. Each value is stored with a [`Version`] of type `V`, a
+/// [`VectorClock`] by default. Values are timestamped with the [`PhysicalClock`] of type `T`, the
+/// wall clock of the machine by default. Changes to its values and to its vector clock are
+/// appended to the [`Log`] of type `L` before they're applied, [`Volatile`] by default.
+#[derive(Debug)]
+pub struct Process<
+  P = usize,
+  C = u64,
+  R = KeepSiblings,
+  V = VectorClock<P, C>,
+  T = SystemClock,
+  L = Volatile,
+> {
+  process_id: P,
+  /// The processes this process knows to be part of the system.
+  membership: Membership<P>,
+  /// The join of every clock seen by this process. Its entry for this process is the counter of
+  /// the latest write it coordinated, so counters are never reused, even across keys.
+  vector_clock: VectorClock<P, C>,
+  kv: HashMap<String, KvEntry<P, C, V>>,
+  resolver: R,
+  physical_clock: T,
+  log: L,
+}
+
+impl<P: ProcessId, C: Counter, V: Version<P, C>> Process<P, C, KeepSiblings, V> {
+  /// Creates the process identified by `process_id` in a system where it's the only member.
+  pub fn new(process_id: P) -> Self {
+    Self::with_members(process_id.clone(), [process_id])
+  }
+
+  /// Creates the process identified by `process_id` in a system made of `processes`.
+  /// `process_id` is always a member, even if it's not part of `processes`.
+  pub fn with_members(process_id: P, processes: impl IntoIterator<Item = P>) -> Self {
+    let membership = Membership::new(processes.into_iter().chain([process_id.clone()]));
+
+    Self {
+      vector_clock: VectorClock::new(process_id.clone()),
+      process_id,
+      membership,
+      kv: HashMap::new(),
+      resolver: KeepSiblings,
+      physical_clock: SystemClock,
+      log: Volatile,
+    }
+  }
+}
+
+impl<P, C, R, V, T, L> Process<P, C, R, V, T, L>
+where
+  P: ProcessId,
+  C: Counter,
+  R: ConflictResolver<P, C, V>,
+  V: Version<P, C>,
+  T: PhysicalClock,
+  L: Log<P, C, V>,
+{
+  /// Replaces the conflict resolver of the process.
+  pub fn with_resolver<R2: ConflictResolver<P, C, V>>(
+    self,
+    resolver: R2,
+  ) -> Process<P, C, R2, V, T, L> {
+    Process {
+      process_id: self.process_id,
+      membership: self.membership,
+      vector_clock: self.vector_clock,
+      kv: self.kv,
+      resolver,
+      physical_clock: self.physical_clock,
+      log: self.log,
+    }
+  }
+
+  /// Replaces the clock used to timestamp values, like a simulated clock in tests.
+  pub fn with_physical_clock<T2: PhysicalClock>(
+    self,
+    physical_clock: T2,
+  ) -> Process<P, C, R, V, T2, L> {
+    Process {
+      process_id: self.process_id,
+      membership: self.membership,
+      vector_clock: self.vector_clock,
+      kv: self.kv,
+      resolver: self.resolver,
+      physical_clock,
+      log: self.log,
+    }
+  }
+
+  /// Replaces the log of the process and restores the values and the vector clock recorded in it,
+  /// so a restarted process never reuses the counters of writes it coordinated before stopping.
+  ///
+  /// Membership changes are not logged, except for [roll overs](Process::roll_over): a restarted
+  /// process must be created with the same members and retire the same processes again.
+  ///
+  /// Fails if the snapshot or the records of `log` cannot be read, or if they record a roll over
+  /// to an incarnation that cannot be added to the members, in which case the process is dropped.
+  pub fn with_log<L2: Log<P, C, V>>(self, mut log: L2) -> Result<Process<P, C, R, V, T, L2>> {
+    let snapshot = log.latest_snapshot()?;
+    let records = log.records()?;
+
+    let mut process = Process {
+      process_id: self.process_id,
+      membership: self.membership,
+      vector_clock: self.vector_clock,
+      kv: self.kv,
+      resolver: self.resolver,
+      physical_clock: self.physical_clock,
+      log,
+    };
+    if let Some(Snapshot {
+      vector_clock,
+      entries,
+    }) = snapshot
+    {
+      // The snapshot is owned by the incarnation the process had rolled over to when it was taken.
+      if vector_clock.process_id() != &process.process_id {
+        process.use_process_id(vector_clock.process_id().clone())?;
+      }
+      process.vector_clock.join_in_place(&vector_clock);
+      process.kv.extend(entries);
+    }
+    for record in records {
+      process.apply(record)?;
+    }
+
+    Ok(process)
+  }
+
+  /// Returns the log of the process.
+  pub fn log(&self) -> &L {
+    &self.log
+  }
+
+  /// Returns a copy of every entry, tombstones included, and of the vector clock of the process.
+  pub fn snapshot(&self) -> Snapshot<P, C, V> {
+    Snapshot::new(
+      self.vector_clock.clone(),
+      self
+        .kv
+        .iter()
+        .map(|(key, entry)| (key.clone(), entry.clone()))
+        .collect(),
+    )
+  }
+
+  /// Replaces the records of the log with a [`Snapshot`] of the process. The log is also
+  /// compacted before appending a record once its [`Log::should_compact`] returns true.
+  ///
+  /// Fails if the snapshot cannot be saved.
+  pub fn compact(&mut self) -> Result<()> {
+    let snapshot = self.snapshot();
+    self.log.compact(&snapshot)
+  }
+
+  /// Returns the id of this process.
+  pub fn process_id(&self) -> &P {
+    &self.process_id
+  }
+
+  /// Returns the processes this process knows to be part of the system.
+  pub fn membership(&self) -> &Membership<P> {
+    &self.membership
+  }
+
+  /// Returns the join of every clock seen by this process.
+  pub fn vector_clock(&self) -> &VectorClock<P, C> {
+    &self.vector_clock
+  }
+
+  /// Adds the process `process_id` to the system. Clocks are sparse, so the clocks of stored keys
+  /// start covering the new process as soon as one of its writes is seen.
+  ///
+  /// Fails if `process_id` is already a member or belonged to a retired process.
+  pub fn add_process(&mut self, process_id: P) -> Result<()> {
+    self.membership.add(process_id)?;
+    Ok(())
+  }
+
+  /// Retires the process `process_id` from the system and compacts the clocks of stored keys by
+  /// removing its entries. Messages received from now on have their entries for `process_id`
+  /// removed as well.
+  ///
+  /// A process should only be retired once its writes have reached every other process, otherwise
+  /// the compacted clocks could order its concurrent writes as if they had been seen.
+  ///
+  /// Fails if `process_id` is not a member or is the id of this process.
+  pub fn retire_process(&mut self, process_id: &P) -> Result<()> {
+    if process_id == &self.process_id {
+      return Err(Error::InvalidProcessId(format!("{process_id:?}")));
+    }
+
+    self.membership.retire(process_id)?;
+
+    self
+      .vector_clock
+      .retain_processes(|other_process_id| other_process_id != process_id);
+    for entry in self.kv.values_mut() {
+      entry.retain_processes(|other_process_id| other_process_id != process_id);
+    }
+
+    Ok(())
+  }
+
+  /// Returns the entry stored for `key`, including the version of each of its values.
+  ///
+  /// Fails if there's no entry for `key`.
+  pub fn entry(&self, key: &str) -> Result<&KvEntry<P, C, V>> {
+    self
+      .kv
+      .get(key)
+      .ok_or_else(|| Error::UnknownKey(key.to_owned()))
+  }
+
+  /// Returns the keys that have an entry, including deleted keys whose tombstones haven't been
+  /// purged yet, in no particular order.
+  pub fn keys(&self) -> impl Iterator<Item = &String> {
+    self.kv.keys()
+  }
+
+  /// Returns every value stored for `key` together with the causal context to pass to
+  /// [`Process::put`] when writing to `key` after this read.
+  ///
+  /// Fails if there's no entry for `key` or if it has been deleted.
+  pub fn get(&self, key: &str) -> Result<GetResult<P, C>> {
+    let entry = self.entry(key)?;
+    if entry.is_deleted() {
+      return Err(Error::UnknownKey(key.to_owned()));
+    }
+
+    Ok(GetResult {
+      values: entry.values().cloned().collect(),
+      context: CausalContext::new(entry.context()),
+    })
+  }
+
+  /// Writes `value` to `key`. Values described by `context`, the context of an earlier read or
+  /// write, are superseded by the new value; any other value stored for `key` is kept as a sibling.
+  /// Returns the context of the new value. When that context would also describe a concurrent value
+  /// the writer hasn't seen, `context` is returned instead and the key must be read again to
+  /// supersede the new value.
+  ///
+  /// Fails if the logical clock of this process overflows, in which case the write is not applied.
+  pub fn put(
+    &mut self,
+    key: &str,
+    value: String,
+    context: CausalContext<P, C>,
+  ) -> Result<CausalContext<P, C>> {
+    let (vector_clock, _) = self.write(Message {
+      vector_clock: context.into_vector_clock(&self.process_id),
+      key: key.to_owned(),
+      value: Some(value),
+    })?;
+
+    Ok(CausalContext::new(vector_clock))
+  }
+
+  /// Deletes `key` by writing a tombstone. Values described by `context` are superseded by the
+  /// tombstone, any other value stored for `key` is kept. Returns the context of the tombstone.
+  ///
+  /// Fails if the logical clock of this process overflows, in which case the delete is not applied.
+  pub fn delete(&mut self, key: &str, context: CausalContext<P, C>) -> Result<CausalContext<P, C>> {
+    let (vector_clock, _) = self.write(Message {
+      vector_clock: context.into_vector_clock(&self.process_id),
+      key: key.to_owned(),
+      value: None,
+    })?;
+
+    Ok(CausalContext::new(vector_clock))
+  }
+
+  /// Returns true when this process has seen every value of `key` seen by `session`, so reading
+  /// `key` from it keeps the guarantees of the session.
+  ///
+  /// A process without an entry for `key` hasn't caught up with a session that has seen `key`,
+  /// even if its tombstone has been purged. Sessions that saw a purged key should
+  /// [forget](Session::forget) it.
+  pub fn has_caught_up(&self, key: &str, session: &Session<P, C>) -> bool {
+    let Some(seen) = session.vector_clock(key) else {
+      return true;
+    };
+
+    self
+      .kv
+      .get(key)
+      .is_some_and(|entry| entry.context().dominates(seen))
+  }
+
+  /// Like [`Process::get`], but for a client in `session`: reads never return values older than
+  /// the ones the session has read or written. The values read, or the tombstone of a deleted key,
+  /// are recorded in `session`.
+  ///
+  /// Fails with [`Error::NotCaughtUp`] if this process hasn't seen every value of `key` seen by
+  /// `session`, in which case the read can be retried later or sent to another process.
+  pub fn session_get(&self, key: &str, session: &mut Session<P, C>) -> Result<GetResult<P, C>> {
+    if !self.has_caught_up(key, session) {
+      return Err(Error::NotCaughtUp(key.to_owned()));
+    }
+
+    session.observe(key, &self.entry(key)?.context());
+    self.get(key)
+  }
+
+  /// Like [`Process::put`], but for a client in `session`: the new value is recorded in `session`,
+  /// so later reads in the session see it or a newer value.
+  ///
+  /// Fails if the logical clock of this process overflows, in which case the write is not applied.
+  pub fn session_put(
+    &mut self,
+    key: &str,
+    value: String,
+    context: CausalContext<P, C>,
+    session: &mut Session<P, C>,
+  ) -> Result<CausalContext<P, C>> {
+    let (vector_clock, version_vector_clock) = self.write(Message {
+      vector_clock: context.into_vector_clock(&self.process_id),
+      key: key.to_owned(),
+      value: Some(value),
+    })?;
+
+    session.observe(key, &version_vector_clock);
+    Ok(CausalContext::new(vector_clock))
+  }
+
+  /// Like [`Process::delete`], but for a client in `session`: the tombstone is recorded in
+  /// `session`, so later reads in the session don't return the deleted values.
+  ///
+  /// Fails if the logical clock of this process overflows, in which case the delete is not applied.
+  pub fn session_delete(
+    &mut self,
+    key: &str,
+    context: CausalContext<P, C>,
+    session: &mut Session<P, C>,
+  ) -> Result<CausalContext<P, C>> {
+    let (vector_clock, version_vector_clock) = self.write(Message {
+      vector_clock: context.into_vector_clock(&self.process_id),
+      key: key.to_owned(),
+      value: None,
+    })?;
+
+    session.observe(key, &version_vector_clock);
+    Ok(CausalContext::new(vector_clock))
+  }
+
+  /// Records that the process `process_id` has seen `key` up to `vector_clock`, usually the
+  /// [`KvEntry::context`] of its copy of the key. Acknowledgments of keys this process doesn't
+  /// store are ignored.
+  ///
+  /// Fails if `process_id` is not a member.
+  pub fn acknowledge(
+    &mut self,
+    process_id: &P,
+    key: &str,
+    vector_clock: &VectorClock<P, C>,
+  ) -> Result<()> {
+    if !self.membership.is_member(process_id) {
+      return Err(Error::UnknownProcess(format!("{process_id:?}")));
+    }
+
+    if let Some(entry) = self.kv.get(key) {
+      let mut entry = entry.clone();
+      entry.acknowledge(process_id, vector_clock);
+      self.commit(LogRecord::Entry {
+        key: key.to_owned(),
+        entry,
+        vector_clock: self.vector_clock.clone(),
+      })?;
+    }
+
+    Ok(())
+  }
+
+  /// Purges every tombstone that all the other members have acknowledged with a clock that
+  /// dominates it, and removes keys left without values. Returns how many tombstones were purged.
+  ///
+  /// Once every process has seen a delete, none of them can bring back the values it superseded,
+  /// so the tombstone is no longer needed.
+  ///
+  /// Fails if the purge of a key cannot be logged, in which case that key and the keys after it
+  /// are left untouched.
+  pub fn collect_garbage(&mut self) -> Result<usize> {
+    let other_processes: Vec<P> = self
+      .membership
+      .members()
+      .filter(|process_id| *process_id != &self.process_id)
+      .cloned()
+      .collect();
+
+    self.purge(|entry| entry.collect_garbage(other_processes.iter()))
+  }
+
+  /// Purges every tombstone covered by `stable`, the events every member is known to have seen,
+  /// and removes keys left without values. Returns how many tombstones were purged.
+  ///
+  /// Unlike [`Process::collect_garbage`], which relies on per key acknowledgments, `stable` is
+  /// usually the [`MatrixClock::stable`](crate::MatrixClock::stable) clock of a matrix clock that
+  /// [observes](crate::MatrixClock::observe) the [`Process::vector_clock`] of this process and is
+  /// exchanged with every message.
+  ///
+  /// Fails if the purge of a key cannot be logged, in which case that key and the keys after it
+  /// are left untouched.
+  pub fn collect_stable_garbage(&mut self, stable: &VectorClock<P, C>) -> Result<usize> {
+    self.purge(|entry| entry.purge_tombstones(Some(stable)))
+  }
+
+  /// Applies the write carried by `message` to the local copy of the key-value store. Stored
+  /// values the writer hadn't seen, according to the vector clock of the message, are kept as
+  /// siblings of the new value.
+  ///
+  /// Fails if the logical clock of this process overflows, in which case the write is not applied.
+  pub fn receive_message(&mut self, message: Message<P, C>) -> Result<()> {
+    self.write(message)?;
+    Ok(())
+  }
+
+  /// Merges `entry`, the copy of `key` stored by another process, into the local copy. Used for
+  /// anti-entropy: values the local copy is missing are added as siblings and values superseded
+  /// by the other copy are dropped.
+  ///
+  /// Fails if the logical clock of this process overflows while writing the value merged by the
+  /// conflict resolver, in which case the entry is not merged.
+  pub fn merge_entry(&mut self, key: &str, entry: &KvEntry<P, C, V>) -> Result<()> {
+    if entry.is_empty() {
+      return Ok(());
+    }
+
+    let mut vector_clock = self.vector_clock.clone();
+    let mut merged = self.kv.get(key).cloned().unwrap_or_else(KvEntry::new);
+    merged.merge(entry);
+    merged.retain_processes(|process_id| !self.membership.is_retired(process_id));
+    vector_clock.join_in_place(&merged.context());
+
+    if let Some(value) = merged.resolve(key, &self.resolver) {
+      let context = merged.context();
+      let version = next_write_version(&mut vector_clock, &self.process_id, &context)?;
+      merged.write(
+        &self.process_id,
+        version,
+        &context,
+        Some(value),
+        self.physical_clock.now(),
+      );
+    }
+
+    self.commit(LogRecord::Entry {
+      key: key.to_owned(),
+      entry: merged,
+      vector_clock,
+    })
+  }
+
+  /// Applies the write carried by `message` and returns the context of the new value together with
+  /// the vector clock of its version.
+  fn write(
+    &mut self,
+    mut message: Message<P, C>,
+  ) -> Result<(VectorClock<P, C>, VectorClock<P, C>)> {
+    message
+      .vector_clock
+      .retain_processes(|process_id| !self.membership.is_retired(process_id));
+
+    let timestamp = self.physical_clock.now();
+
+    // The write is applied to copies so that a failure leaves the process untouched.
+    let mut vector_clock = self.vector_clock.clone();
+    let mut entry = self
+      .kv
+      .get(&message.key)
+      .cloned()
+      .unwrap_or_else(KvEntry::new);
+
+    let version: V =
+      next_write_version(&mut vector_clock, &self.process_id, &message.vector_clock)?;
+    // The smallest clock covering the write may also cover concurrent values written through this
+    // process, in which case only the context of the writer is safe to hand back.
+    let version_vector_clock = version.to_vector_clock();
+    let mut write_vector_clock = version_vector_clock.clone();
+    entry.write(
+      &self.process_id,
+      version,
+      &message.vector_clock,
+      message.value,
+      timestamp,
+    );
+    let (_, others) = entry
+      .siblings()
+      .split_last()
+      .expect("the write was just added");
+    if others
+      .iter()
+      .any(|sibling| sibling.version.is_covered_by(&write_vector_clock))
+    {
+      write_vector_clock = message.vector_clock.clone();
+    }
+
+    if let Some(value) = entry.resolve(&message.key, &self.resolver) {
+      let context = entry.context();
+      let version = next_write_version(&mut vector_clock, &self.process_id, &context)?;
+      entry.write(&self.process_id, version, &context, Some(value), timestamp);
+    }
+
+    self.commit(LogRecord::Entry {
+      key: message.key,
+      entry,
+      vector_clock,
+    })?;
+
+    Ok((write_vector_clock, version_vector_clock))
+  }
+
+  /// Purges tombstones from every entry with `purge_tombstones`, which returns how many it purged,
+  /// and removes keys left without values. Returns how many tombstones were purged.
+  fn purge(
+    &mut self,
+    mut purge_tombstones: impl FnMut(&mut KvEntry<P, C, V>) -> usize,
+  ) -> Result<usize> {
+    let mut purged = 0;
+
+    let keys: Vec<String> = self.kv.keys().cloned().collect();
+    for key in keys {
+      let mut entry = self.kv[&key].clone();
+      let entry_purged = purge_tombstones(&mut entry);
+      if entry_purged == 0 {
+        continue;
+      }
+
+      let record = if entry.is_empty() {
+        LogRecord::Remove { key }
+      } else {
+        LogRecord::Entry {
+          key,
+          entry,
+          vector_clock: self.vector_clock.clone(),
+        }
+      };
+      self.commit(record)?;
+      purged += entry_purged;
+    }
+
+    Ok(purged)
+  }
+
+  /// Appends `record` to the log and applies it, or leaves the process untouched if it cannot be
+  /// logged.
+  fn commit(&mut self, record: LogRecord<P, C, V>) -> Result<()> {
+    if self.log.should_compact() {
+      self.compact()?;
+    }
+    self.log.append(&record)?;
+    self.apply(record)
+  }
+
+  /// Applies `record` to the process.
+  ///
+  /// Fails if `record` moves the process to an id that cannot be added to the members.
+  fn apply(&mut self, record: LogRecord<P, C, V>) -> Result<()> {
+    match record {
+      LogRecord::Entry {
+        key,
+        entry,
+        vector_clock,
+      } => {
+        self.vector_clock.join_in_place(&vector_clock);
+        self.kv.insert(key, entry);
+      }
+      LogRecord::Remove { key } => {
+        self.kv.remove(&key);
+      }
+      LogRecord::ProcessId { process_id } => self.use_process_id(process_id)?,
+    }
+    Ok(())
+  }
+
+  /// Makes `process_id` the id of the process and of its vector clock, adding it to the members
+  /// if it isn't one yet.
+  ///
+  /// Fails if `process_id` isn't a member and cannot be added, in which case the process is left
+  /// untouched.
+  fn use_process_id(&mut self, process_id: P) -> Result<()> {
+    if !self.membership.is_member(&process_id) {
+      self.membership.add(process_id.clone())?;
+    }
+    self.vector_clock.set_process_id(process_id.clone());
+    self.process_id = process_id;
+    Ok(())
+  }
+}
+
+/// Returns the version of a new write coordinated by `process_id` from a writer that had seen
+/// `context`. `vector_clock` is the clock of the process, which is updated to account for the
+/// write.
+fn next_write_version<P: ProcessId, C: Counter, V: Version<P, C>>(
+  vector_clock: &mut VectorClock<P, C>,
+  process_id: &P,
+  context: &VectorClock<P, C>,
+) -> Result<V> {
+  vector_clock.join_in_place(context);
+  vector_clock.increment_process_clock(process_id)?;
+
+  let version = V::for_write(process_id, vector_clock.clock(process_id), context)?;
+  // Versions like Lamport clocks can count past the counter of the process, whose counters must
+  // never be reused either.
+  vector_clock.join_in_place(&version.to_vector_clock());
+  Ok(version)
+}
+
+impl<P, C, R, V, T, L> Process<Incarnation<P>, C, R, V, T, L>
+where
+  P: ProcessId,
+  C: Counter,
+  R: ConflictResolver<Incarnation<P>, C, V>,
+  V: Version<Incarnation<P>, C>,
+  T: PhysicalClock,
+  L: Log<Incarnation<P>, C, V>,
+{
+  /// Starts a new epoch for this process: the next incarnation of its process id joins the system
+  /// and becomes the id of this process, so its writes are counted from zero again. Meant to be
+  /// called once the counter of the current incarnation is about to overflow, or after a write
+  /// failed with [`Error::CounterOverflow`].
+  ///
+  /// The previous incarnation stays a member so that its events keep being tracked. Retire it with
+  /// [`Process::retire_process`] once its writes have reached every other process.
+  ///
+  /// Fails if the next incarnation is already a member or has been retired, or if the roll over
+  /// cannot be logged.
+  pub fn roll_over(&mut self) -> Result<()> {
+    let next_incarnation = self.process_id.next();
+    self.membership.can_add(&next_incarnation)?;
+    self.commit(LogRecord::ProcessId {
+      process_id: next_incarnation,
+    })
+  }
+}
@@ -0,0 +1,7 @@
+use std::{fmt::Debug, hash::Hash};
+
+/// Identifies a process of the system. Process ids can be dense indices like `0..n`, host names,
+/// UUIDs or anything else that can be ordered, hashed and cloned.
+pub trait ProcessId: Ord + Hash + Clone + Debug {}
+
+impl<T> ProcessId for T where T: Ord + Hash + Clone + Debug {}
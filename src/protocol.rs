@@ -0,0 +1,509 @@
+use std::{
+  io::{ErrorKind, Read, Write},
+  marker::PhantomData,
+  net::{TcpStream, ToSocketAddrs},
+};
+
+use crate::{
+  CausalContext, Counter, Error, GetResult, KvEntry, Message, ProcessId, Result, Session,
+  VectorClock, Version, WireFormat,
+};
+
+/// The largest frame accepted by [`read_frame`], so a corrupted length prefix cannot make a
+/// process allocate an arbitrary amount of memory.
+pub const MAX_FRAME_LEN: u32 = 16 * 1024 * 1024;
+
+/// A request sent to a process over the network, by a client or by another process.
+///
+/// Its [`WireFormat`] is a tag byte, the index of the variant in declaration order, followed by
+/// its fields in order.
+#[derive(Debug, Clone)]
+pub enum Request<P = usize, C = u64, V = VectorClock<P, C>> {
+  /// Reads the values of a key, see [`Process::get`](crate::Process::get).
+  Get { key: String },
+  /// Writes a value to a key, see [`Process::put`](crate::Process::put).
+  Put {
+    key: String,
+    value: String,
+    context: CausalContext<P, C>,
+  },
+  /// Deletes a key, see [`Process::delete`](crate::Process::delete).
+  Delete {
+    key: String,
+    context: CausalContext<P, C>,
+  },
+  /// A write forwarded by another process, see
+  /// [`Process::receive_message`](crate::Process::receive_message).
+  Message(Message<P, C>),
+  /// The entry of a key sent by another process to be merged with the local one, see
+  /// [`Process::merge_entry`](crate::Process::merge_entry).
+  Replicate {
+    key: String,
+    entry: KvEntry<P, C, V>,
+  },
+  /// Reads the values of a key for a client in a session, see
+  /// [`Process::session_get`](crate::Process::session_get). Only the part of the session about
+  /// the key needs to be sent.
+  SessionGet { key: String, session: Session<P, C> },
+  /// Writes a value to a key for a client in a session, see
+  /// [`Process::session_put`](crate::Process::session_put).
+  SessionPut {
+    key: String,
+    value: String,
+    context: CausalContext<P, C>,
+    session: Session<P, C>,
+  },
+  /// Deletes a key for a client in a session, see
+  /// [`Process::session_delete`](crate::Process::session_delete).
+  SessionDelete {
+    key: String,
+    context: CausalContext<P, C>,
+    session: Session<P, C>,
+  },
+}
+
+/// The response to a [`Request`].
+///
+/// Encoded like a [`Request`]. Errors are a tag byte, the index of the [`Error`] variant in
+/// declaration order, followed by its message.
+#[derive(Debug, Clone)]
+pub enum Response<P = usize, C = u64> {
+  /// The response to [`Request::Get`].
+  Values(GetResult<P, C>),
+  /// The response to [`Request::Put`] and [`Request::Delete`]: the context of the write.
+  Context(CausalContext<P, C>),
+  /// The response to [`Request::Message`] and [`Request::Replicate`].
+  Done,
+  /// The request failed.
+  Error(Error),
+  /// The response to [`Request::SessionGet`]: the values read and the session of the request
+  /// updated with them.
+  SessionValues(GetResult<P, C>, Session<P, C>),
+  /// The response to [`Request::SessionPut`] and [`Request::SessionDelete`]: the context of the
+  /// write and the session of the request updated with it.
+  SessionContext(CausalContext<P, C>, Session<P, C>),
+}
+
+/// Writes `value` as a frame: its [`WireFormat`] bytes prefixed by their length as a 32 bit big
+/// endian integer.
+pub fn write_frame<T: WireFormat>(writer: &mut impl Write, value: &T) -> Result<()> {
+  let bytes = value.to_bytes();
+  let len = u32::try_from(bytes.len())
+    .ok()
+    .filter(|len| *len <= MAX_FRAME_LEN)
+    .ok_or_else(|| {
+      Error::InvalidEncoding(format!("frame of {} bytes is too large", bytes.len()))
+    })?;
+  writer.write_all(&len.to_be_bytes())?;
+  writer.write_all(&bytes)?;
+  writer.flush()?;
+  Ok(())
+}
+
+/// Reads a frame written by [`write_frame`].
+///
+/// Returns `None` if the reader is closed before the first byte of the frame. Fails if it's
+/// closed in the middle of the frame, or if the frame is larger than [`MAX_FRAME_LEN`].
+pub fn read_frame<T: WireFormat>(reader: &mut impl Read) -> Result<Option<T>> {
+  let mut len = [0; 4];
+  loop {
+    match reader.read(&mut len[..1]) {
+      Ok(0) => return Ok(None),
+      Ok(_) => break,
+      Err(error) if error.kind() == ErrorKind::Interrupted => {}
+      Err(error) => return Err(error.into()),
+    }
+  }
+  reader.read_exact(&mut len[1..])?;
+
+  let len = u32::from_be_bytes(len);
+  if len > MAX_FRAME_LEN {
+    return Err(Error::InvalidEncoding(format!(
+      "frame of {len} bytes is too large"
+    )));
+  }
+
+  let mut bytes = vec![0; len as usize];
+  reader.read_exact(&mut bytes)?;
+  T::from_bytes(&bytes).map(Some)
+}
+
+/// A connection to a process, used to send it requests.
+#[derive(Debug)]
+pub struct Client<P = usize, C = u64> {
+  stream: TcpStream,
+  _marker: PhantomData<(P, C)>,
+}
+
+impl<P, C> Client<P, C>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+{
+  /// Connects to the process listening on `address`.
+  pub fn connect(address: impl ToSocketAddrs) -> Result<Self> {
+    let stream = TcpStream::connect(address)?;
+    stream.set_nodelay(true)?;
+    Ok(Self {
+      stream,
+      _marker: PhantomData,
+    })
+  }
+
+  /// Sends a request and waits for its response.
+  ///
+  /// [`Response::Error`] is returned as an error.
+  pub fn send<V: Version<P, C> + WireFormat>(
+    &mut self,
+    request: &Request<P, C, V>,
+  ) -> Result<Response<P, C>> {
+    write_frame(&mut self.stream, request)?;
+    match read_frame(&mut self.stream)? {
+      None => Err(Error::Io("connection closed by the process".to_owned())),
+      Some(Response::Error(error)) => Err(error),
+      Some(response) => Ok(response),
+    }
+  }
+
+  /// Reads the values of `key`, see [`Process::get`](crate::Process::get).
+  pub fn get(&mut self, key: &str) -> Result<GetResult<P, C>> {
+    let request: Request<P, C> = Request::Get {
+      key: key.to_owned(),
+    };
+    match self.send(&request)? {
+      Response::Values(result) => Ok(result),
+      response => Err(unexpected_response(&response)),
+    }
+  }
+
+  /// Writes `value` to `key`, see [`Process::put`](crate::Process::put).
+  pub fn put(
+    &mut self,
+    key: &str,
+    value: String,
+    context: CausalContext<P, C>,
+  ) -> Result<CausalContext<P, C>> {
+    let request: Request<P, C> = Request::Put {
+      key: key.to_owned(),
+      value,
+      context,
+    };
+    match self.send(&request)? {
+      Response::Context(context) => Ok(context),
+      response => Err(unexpected_response(&response)),
+    }
+  }
+
+  /// Deletes `key`, see [`Process::delete`](crate::Process::delete).
+  pub fn delete(&mut self, key: &str, context: CausalContext<P, C>) -> Result<CausalContext<P, C>> {
+    let request: Request<P, C> = Request::Delete {
+      key: key.to_owned(),
+      context,
+    };
+    match self.send(&request)? {
+      Response::Context(context) => Ok(context),
+      response => Err(unexpected_response(&response)),
+    }
+  }
+
+  /// Reads the values of `key` in `session`, see
+  /// [`Process::session_get`](crate::Process::session_get).
+  pub fn session_get(&mut self, key: &str, session: &mut Session<P, C>) -> Result<GetResult<P, C>> {
+    let request: Request<P, C> = Request::SessionGet {
+      key: key.to_owned(),
+      session: session.for_key(key),
+    };
+    match self.send(&request)? {
+      Response::SessionValues(result, seen) => {
+        session.join_in_place(&seen);
+        Ok(result)
+      }
+      response => Err(unexpected_response(&response)),
+    }
+  }
+
+  /// Writes `value` to `key` in `session`, see
+  /// [`Process::session_put`](crate::Process::session_put).
+  pub fn session_put(
+    &mut self,
+    key: &str,
+    value: String,
+    context: CausalContext<P, C>,
+    session: &mut Session<P, C>,
+  ) -> Result<CausalContext<P, C>> {
+    let request: Request<P, C> = Request::SessionPut {
+      key: key.to_owned(),
+      value,
+      context,
+      session: session.for_key(key),
+    };
+    match self.send(&request)? {
+      Response::SessionContext(context, seen) => {
+        session.join_in_place(&seen);
+        Ok(context)
+      }
+      response => Err(unexpected_response(&response)),
+    }
+  }
+
+  /// Deletes `key` in `session`, see
+  /// [`Process::session_delete`](crate::Process::session_delete).
+  pub fn session_delete(
+    &mut self,
+    key: &str,
+    context: CausalContext<P, C>,
+    session: &mut Session<P, C>,
+  ) -> Result<CausalContext<P, C>> {
+    let request: Request<P, C> = Request::SessionDelete {
+      key: key.to_owned(),
+      context,
+      session: session.for_key(key),
+    };
+    match self.send(&request)? {
+      Response::SessionContext(context, seen) => {
+        session.join_in_place(&seen);
+        Ok(context)
+      }
+      response => Err(unexpected_response(&response)),
+    }
+  }
+}
+
+fn unexpected_response<P, C>(response: &Response<P, C>) -> Error {
+  let name = match response {
+    Response::Values(_) => "values",
+    Response::Context(_) => "context",
+    Response::Done => "done",
+    Response::Error(_) => "error",
+    Response::SessionValues(..) => "session values",
+    Response::SessionContext(..) => "session context",
+  };
+  Error::InvalidEncoding(format!("unexpected {name} response"))
+}
+
+impl<P, C, V> WireFormat for Request<P, C, V>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+  V: Version<P, C> + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    match self {
+      Request::Get { key } => {
+        buffer.push(0);
+        key.encode_into(buffer);
+      }
+      Request::Put {
+        key,
+        value,
+        context,
+      } => {
+        buffer.push(1);
+        key.encode_into(buffer);
+        value.encode_into(buffer);
+        context.encode_into(buffer);
+      }
+      Request::Delete { key, context } => {
+        buffer.push(2);
+        key.encode_into(buffer);
+        context.encode_into(buffer);
+      }
+      Request::Message(message) => {
+        buffer.push(3);
+        message.encode_into(buffer);
+      }
+      Request::Replicate { key, entry } => {
+        buffer.push(4);
+        key.encode_into(buffer);
+        entry.encode_into(buffer);
+      }
+      Request::SessionGet { key, session } => {
+        buffer.push(5);
+        key.encode_into(buffer);
+        session.encode_into(buffer);
+      }
+      Request::SessionPut {
+        key,
+        value,
+        context,
+        session,
+      } => {
+        buffer.push(6);
+        key.encode_into(buffer);
+        value.encode_into(buffer);
+        context.encode_into(buffer);
+        session.encode_into(buffer);
+      }
+      Request::SessionDelete {
+        key,
+        context,
+        session,
+      } => {
+        buffer.push(7);
+        key.encode_into(buffer);
+        context.encode_into(buffer);
+        session.encode_into(buffer);
+      }
+    }
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(match u8::decode_from(input)? {
+      0 => Request::Get {
+        key: String::decode_from(input)?,
+      },
+      1 => Request::Put {
+        key: String::decode_from(input)?,
+        value: String::decode_from(input)?,
+        context: CausalContext::decode_from(input)?,
+      },
+      2 => Request::Delete {
+        key: String::decode_from(input)?,
+        context: CausalContext::decode_from(input)?,
+      },
+      3 => Request::Message(Message::decode_from(input)?),
+      4 => Request::Replicate {
+        key: String::decode_from(input)?,
+        entry: KvEntry::decode_from(input)?,
+      },
+      5 => Request::SessionGet {
+        key: String::decode_from(input)?,
+        session: Session::decode_from(input)?,
+      },
+      6 => Request::SessionPut {
+        key: String::decode_from(input)?,
+        value: String::decode_from(input)?,
+        context: CausalContext::decode_from(input)?,
+        session: Session::decode_from(input)?,
+      },
+      7 => Request::SessionDelete {
+        key: String::decode_from(input)?,
+        context: CausalContext::decode_from(input)?,
+        session: Session::decode_from(input)?,
+      },
+      tag => return Err(Error::InvalidEncoding(format!("invalid request tag {tag}"))),
+    })
+  }
+}
+
+impl<P, C> WireFormat for Response<P, C>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    match self {
+      Response::Values(result) => {
+        buffer.push(0);
+        result.encode_into(buffer);
+      }
+      Response::Context(context) => {
+        buffer.push(1);
+        context.encode_into(buffer);
+      }
+      Response::Done => buffer.push(2),
+      Response::Error(error) => {
+        buffer.push(3);
+        error.encode_into(buffer);
+      }
+      Response::SessionValues(result, session) => {
+        buffer.push(4);
+        result.encode_into(buffer);
+        session.encode_into(buffer);
+      }
+      Response::SessionContext(context, session) => {
+        buffer.push(5);
+        context.encode_into(buffer);
+        session.encode_into(buffer);
+      }
+    }
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(match u8::decode_from(input)? {
+      0 => Response::Values(GetResult::decode_from(input)?),
+      1 => Response::Context(CausalContext::decode_from(input)?),
+      2 => Response::Done,
+      3 => Response::Error(Error::decode_from(input)?),
+      4 => Response::SessionValues(GetResult::decode_from(input)?, Session::decode_from(input)?),
+      5 => Response::SessionContext(
+        CausalContext::decode_from(input)?,
+        Session::decode_from(input)?,
+      ),
+      tag => {
+        return Err(Error::InvalidEncoding(format!(
+          "invalid response tag {tag}"
+        )))
+      }
+    })
+  }
+}
+
+impl<P, C> WireFormat for GetResult<P, C>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.values.len().encode_into(buffer);
+    for value in &self.values {
+      value.encode_into(buffer);
+    }
+    self.context.encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    let len = usize::decode_from(input)?;
+    let mut values = Vec::new();
+    for _ in 0..len {
+      values.push(String::decode_from(input)?);
+    }
+    Ok(GetResult {
+      values,
+      context: CausalContext::decode_from(input)?,
+    })
+  }
+}
+
+impl WireFormat for Error {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    let (tag, reason) = match self {
+      Error::UnknownProcess(reason) => (0, reason),
+      Error::DuplicateProcess(reason) => (1, reason),
+      Error::RetiredProcess(reason) => (2, reason),
+      Error::InvalidProcessId(reason) => (3, reason),
+      Error::UnknownKey(reason) => (4, reason),
+      Error::CounterOverflow(reason) => (5, reason),
+      Error::ClockDrift(reason) => (6, reason),
+      Error::InvalidConfiguration(reason) => (7, reason),
+      Error::Io(reason) => (8, reason),
+      Error::QueueFull(reason) => (9, reason),
+      Error::InvalidStamp(reason) => (10, reason),
+      Error::InvalidEncoding(reason) => (11, reason),
+      Error::NotCaughtUp(reason) => (12, reason),
+    };
+    buffer.push(tag);
+    reason.encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    let tag = u8::decode_from(input)?;
+    let reason = String::decode_from(input)?;
+    Ok(match tag {
+      0 => Error::UnknownProcess(reason),
+      1 => Error::DuplicateProcess(reason),
+      2 => Error::RetiredProcess(reason),
+      3 => Error::InvalidProcessId(reason),
+      4 => Error::UnknownKey(reason),
+      5 => Error::CounterOverflow(reason),
+      6 => Error::ClockDrift(reason),
+      7 => Error::InvalidConfiguration(reason),
+      8 => Error::Io(reason),
+      9 => Error::QueueFull(reason),
+      10 => Error::InvalidStamp(reason),
+      11 => Error::InvalidEncoding(reason),
+      12 => Error::NotCaughtUp(reason),
+      tag => return Err(Error::InvalidEncoding(format!("invalid error tag {tag}"))),
+    })
+  }
+}
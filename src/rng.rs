@@ -0,0 +1,45 @@
+/// A seeded [splitmix64](https://prng.di.unimi.it/splitmix64.c) generator: small, fast and good
+/// enough to drive simulations. It draws the random choices of a [`Simulator`](crate::Simulator),
+/// and can draw random histories for tests and benchmarks that must be reproducible from a seed.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+  /// Creates a generator whose numbers are drawn from `seed`.
+  pub fn new(seed: u64) -> Self {
+    Self(seed)
+  }
+
+  /// Returns the next number.
+  pub fn next_u64(&mut self) -> u64 {
+    self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.0;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  /// Returns a number in `[0, 1)`.
+  pub fn next_f64(&mut self) -> f64 {
+    (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+  }
+
+  /// Returns a number in `[min, max]`.
+  pub fn between(&mut self, min: u64, max: u64) -> u64 {
+    if max <= min {
+      return min;
+    }
+    match (max - min).checked_add(1) {
+      Some(range) => min + self.next_u64() % range,
+      None => self.next_u64(),
+    }
+  }
+
+  /// Returns a number in `[0, n)`, or 0 if `n` is 0.
+  pub fn below(&mut self, n: usize) -> usize {
+    if n == 0 {
+      return 0;
+    }
+    self.between(0, n as u64 - 1) as usize
+  }
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use crate::{Counter, ProcessId, VectorClock};
+
+/// The causal history of a client, used to get read-your-writes and monotonic reads when the
+/// client talks to different processes.
+///
+/// The session carries, for every key the client has read or written, the join of the vector
+/// clocks of the values it has seen. Clocks are kept per key because processes replicate keys
+/// independently: a process may have seen later writes to one key without having seen earlier
+/// writes to another one.
+///
+/// Pass the session to [`Process::session_get`](crate::Process::session_get),
+/// [`Process::session_put`](crate::Process::session_put) and
+/// [`Process::session_delete`](crate::Process::session_delete), or to the methods of the same
+/// name of [`Client`](crate::Client). Reads from a process that hasn't caught up with the session
+/// fail with [`Error::NotCaughtUp`](crate::Error::NotCaughtUp).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(bound(deserialize = "P: crate::ProcessId + serde::Deserialize<'de>, \
+    C: crate::Counter + serde::Deserialize<'de>"))
+)]
+pub struct Session<P = usize, C = u64> {
+  vector_clocks: BTreeMap<String, VectorClock<P, C>>,
+}
+
+impl<P: ProcessId, C: Counter> Session<P, C> {
+  /// Creates a session that hasn't seen any value.
+  pub fn new() -> Self {
+    Self {
+      vector_clocks: BTreeMap::new(),
+    }
+  }
+
+  /// Returns the join of the vector clocks of every value of `key` seen by the session, `None` if
+  /// the session hasn't read or written `key`.
+  pub fn vector_clock(&self, key: &str) -> Option<&VectorClock<P, C>> {
+    self.vector_clocks.get(key)
+  }
+
+  /// Returns the keys read or written by the session.
+  pub fn keys(&self) -> impl Iterator<Item = &String> {
+    self.vector_clocks.keys()
+  }
+
+  /// Forgets the values of `key` seen by the session, like once its tombstone has been purged from
+  /// every process with [`Process::collect_garbage`](crate::Process::collect_garbage).
+  pub fn forget(&mut self, key: &str) {
+    self.vector_clocks.remove(key);
+  }
+
+  /// Records that the session has seen the values of `key` described by `vector_clock`.
+  pub(crate) fn observe(&mut self, key: &str, vector_clock: &VectorClock<P, C>) {
+    match self.vector_clocks.get_mut(key) {
+      Some(seen) => seen.join_in_place(vector_clock),
+      None => {
+        self
+          .vector_clocks
+          .insert(key.to_owned(), vector_clock.clone());
+      }
+    }
+  }
+
+  /// Joins the history of `other` into the session.
+  pub(crate) fn join_in_place(&mut self, other: &Session<P, C>) {
+    for (key, vector_clock) in &other.vector_clocks {
+      self.observe(key, vector_clock);
+    }
+  }
+
+  /// Returns the part of the session that's about `key`, the only part a process needs to serve
+  /// a request for `key`.
+  pub(crate) fn for_key(&self, key: &str) -> Session<P, C> {
+    Self {
+      vector_clocks: self
+        .vector_clocks
+        .get_key_value(key)
+        .map(|(key, vector_clock)| (key.clone(), vector_clock.clone()))
+        .into_iter()
+        .collect(),
+    }
+  }
+
+  pub(crate) fn from_vector_clocks(vector_clocks: BTreeMap<String, VectorClock<P, C>>) -> Self {
+    Self { vector_clocks }
+  }
+
+  pub(crate) fn vector_clocks(&self) -> &BTreeMap<String, VectorClock<P, C>> {
+    &self.vector_clocks
+  }
+}
+
+impl<P: ProcessId, C: Counter> Default for Session<P, C> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
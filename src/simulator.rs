@@ -0,0 +1,299 @@
+use std::{cell::Cell, collections::BTreeMap, rc::Rc};
+
+use crate::{
+  CausalContext, ConflictResolver, Error, GetResult, KeepSiblings, KvEntry, PhysicalClock, Process,
+  Result, Rng, VectorClock, Version,
+};
+
+/// The behaviour of the virtual network of a [`Simulator`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkConfig {
+  /// The smallest delay of a message, in milliseconds of simulated time.
+  pub min_latency: u64,
+  /// The largest delay of a message, in milliseconds of simulated time. Messages whose delays
+  /// overlap can be delivered in a different order than they were sent.
+  pub max_latency: u64,
+  /// The probability that a message is lost.
+  pub drop_probability: f64,
+  /// The probability that a message is delivered twice.
+  pub duplicate_probability: f64,
+}
+
+impl Default for NetworkConfig {
+  /// A network that delivers every message exactly once, in 1 to 10 milliseconds.
+  fn default() -> Self {
+    Self {
+      min_latency: 1,
+      max_latency: 10,
+      drop_probability: 0.0,
+      duplicate_probability: 0.0,
+    }
+  }
+}
+
+/// The simulated time of a [`Simulator`], shared by every process to timestamp its values.
+#[derive(Debug, Clone, Default)]
+pub struct SimulatedClock(Rc<Cell<u64>>);
+
+impl PhysicalClock for SimulatedClock {
+  fn now(&self) -> u64 {
+    self.0.get()
+  }
+}
+
+/// The processes of a [`Simulator`].
+pub type SimulatedProcess<R = KeepSiblings, V = VectorClock> =
+  Process<usize, u64, R, V, SimulatedClock>;
+
+/// A copy of an entry on its way from one process to another.
+#[derive(Debug)]
+struct Delivery<V> {
+  from: usize,
+  to: usize,
+  key: String,
+  entry: KvEntry<usize, u64, V>,
+}
+
+/// Runs processes `0..n` over a virtual network, deterministically: every run with the same seed
+/// and the same operations delivers the same messages in the same order, so a causality bug found
+/// by a random scenario can be reproduced from its seed.
+///
+/// Every write is replicated by sending the entry of the key to every other process, which merges
+/// it with [`Process::merge_entry`]. Messages are delayed, reordered, duplicated or dropped
+/// according to the [`NetworkConfig`], and messages between processes on different sides of a
+/// partition are dropped when they arrive.
+#[derive(Debug)]
+pub struct Simulator<R = KeepSiblings, V = VectorClock> {
+  processes: Vec<SimulatedProcess<R, V>>,
+  network: NetworkConfig,
+  rng: Rng,
+  clock: SimulatedClock,
+  /// Messages in flight, by delivery time and then by the order they were sent in.
+  in_flight: BTreeMap<(u64, u64), Delivery<V>>,
+  sent: u64,
+  /// The side of the partition of each process. Processes can only talk to processes on their side.
+  sides: Vec<usize>,
+}
+
+impl<V: Version<usize, u64>> Simulator<KeepSiblings, V> {
+  /// Creates a simulation of `processes` processes, all members of the same system, whose random
+  /// choices are drawn from `seed`.
+  pub fn new(seed: u64, processes: usize, network: NetworkConfig) -> Self {
+    let clock = SimulatedClock::default();
+    Self {
+      processes: (0..processes)
+        .map(|process_id| {
+          Process::with_members(process_id, 0..processes).with_physical_clock(clock.clone())
+        })
+        .collect(),
+      network,
+      rng: Rng::new(seed),
+      clock,
+      in_flight: BTreeMap::new(),
+      sent: 0,
+      sides: vec![0; processes],
+    }
+  }
+}
+
+impl<R: ConflictResolver<usize, u64, V>, V: Version<usize, u64>> Simulator<R, V> {
+  /// Replaces the conflict resolver of every process.
+  pub fn with_resolver<R2: ConflictResolver<usize, u64, V> + Clone>(
+    self,
+    resolver: R2,
+  ) -> Simulator<R2, V> {
+    Simulator {
+      processes: self
+        .processes
+        .into_iter()
+        .map(|process| process.with_resolver(resolver.clone()))
+        .collect(),
+      network: self.network,
+      rng: self.rng,
+      clock: self.clock,
+      in_flight: self.in_flight,
+      sent: self.sent,
+      sides: self.sides,
+    }
+  }
+
+  /// Returns the simulated time, in milliseconds.
+  pub fn now(&self) -> u64 {
+    self.clock.now()
+  }
+
+  /// Returns every process, indexed by process id.
+  pub fn processes(&self) -> &[SimulatedProcess<R, V>] {
+    &self.processes
+  }
+
+  /// Returns the process `process_id`.
+  ///
+  /// Fails if there's no such process.
+  pub fn process(&self, process_id: usize) -> Result<&SimulatedProcess<R, V>> {
+    self
+      .processes
+      .get(process_id)
+      .ok_or_else(|| Error::UnknownProcess(format!("{process_id:?}")))
+  }
+
+  /// Returns how many messages are in flight.
+  pub fn in_flight(&self) -> usize {
+    self.in_flight.len()
+  }
+
+  /// Splits the network: processes can only talk to processes of the same group. Processes that
+  /// are not part of any group are isolated from every other process.
+  pub fn partition<G: IntoIterator<Item = usize>>(&mut self, groups: impl IntoIterator<Item = G>) {
+    let isolated = self.processes.len();
+    for (process_id, side) in self.sides.iter_mut().enumerate() {
+      *side = isolated + process_id;
+    }
+    for (group, processes) in groups.into_iter().enumerate() {
+      for process_id in processes {
+        if let Some(side) = self.sides.get_mut(process_id) {
+          *side = group;
+        }
+      }
+    }
+  }
+
+  /// Heals every partition.
+  pub fn heal(&mut self) {
+    self.sides.fill(0);
+  }
+
+  /// Reads `key` from the process `process_id`.
+  ///
+  /// Fails like [`Process::get`] or if there's no such process.
+  pub fn get(&self, process_id: usize, key: &str) -> Result<GetResult<usize, u64>> {
+    self.process(process_id)?.get(key)
+  }
+
+  /// Writes `value` to `key` through the process `process_id` and replicates the entry to every
+  /// other process.
+  ///
+  /// Fails like [`Process::put`] or if there's no such process.
+  pub fn put(
+    &mut self,
+    process_id: usize,
+    key: &str,
+    value: String,
+    context: CausalContext<usize, u64>,
+  ) -> Result<CausalContext<usize, u64>> {
+    let context = self.process_mut(process_id)?.put(key, value, context)?;
+    self.replicate(process_id, key)?;
+    Ok(context)
+  }
+
+  /// Deletes `key` through the process `process_id` and replicates the tombstone to every other
+  /// process.
+  ///
+  /// Fails like [`Process::delete`] or if there's no such process.
+  pub fn delete(
+    &mut self,
+    process_id: usize,
+    key: &str,
+    context: CausalContext<usize, u64>,
+  ) -> Result<CausalContext<usize, u64>> {
+    let context = self.process_mut(process_id)?.delete(key, context)?;
+    self.replicate(process_id, key)?;
+    Ok(context)
+  }
+
+  /// Starts a round of anti-entropy: every process sends every entry it stores to every other
+  /// process.
+  pub fn sync(&mut self) -> Result<()> {
+    for process_id in 0..self.processes.len() {
+      let mut keys: Vec<String> = self.processes[process_id].keys().cloned().collect();
+      // Keys are stored in a hash map, sorting them keeps the simulation deterministic.
+      keys.sort();
+      for key in keys {
+        self.replicate(process_id, &key)?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Delivers the next message in flight, advancing the simulated time to its delivery time.
+  /// Returns false when there are no messages in flight.
+  ///
+  /// Fails if the process the message is delivered to fails to merge it.
+  pub fn step(&mut self) -> Result<bool> {
+    let Some(((at, _), delivery)) = self.in_flight.pop_first() else {
+      return Ok(false);
+    };
+
+    self.clock.0.set(std::cmp::max(self.now(), at));
+    if self.sides[delivery.from] == self.sides[delivery.to] {
+      self.processes[delivery.to].merge_entry(&delivery.key, &delivery.entry)?;
+    }
+
+    Ok(true)
+  }
+
+  /// Delivers messages until none are in flight.
+  ///
+  /// Fails like [`Simulator::step`].
+  pub fn run(&mut self) -> Result<()> {
+    while self.step()? {}
+    Ok(())
+  }
+
+  /// Delivers the messages due up to `time` and advances the simulated time to `time`.
+  ///
+  /// Fails like [`Simulator::step`].
+  pub fn run_until(&mut self, time: u64) -> Result<()> {
+    while self
+      .in_flight
+      .first_key_value()
+      .is_some_and(|((at, _), _)| *at <= time)
+    {
+      self.step()?;
+    }
+    self.clock.0.set(std::cmp::max(self.now(), time));
+    Ok(())
+  }
+
+  fn process_mut(&mut self, process_id: usize) -> Result<&mut SimulatedProcess<R, V>> {
+    self
+      .processes
+      .get_mut(process_id)
+      .ok_or_else(|| Error::UnknownProcess(format!("{process_id:?}")))
+  }
+
+  /// Sends the entry of `key` stored by `process_id` to every other process.
+  fn replicate(&mut self, process_id: usize, key: &str) -> Result<()> {
+    let entry = self.processes[process_id].entry(key)?.clone();
+
+    for to in 0..self.processes.len() {
+      if to == process_id || self.rng.next_f64() < self.network.drop_probability {
+        continue;
+      }
+
+      let copies = if self.rng.next_f64() < self.network.duplicate_probability {
+        2
+      } else {
+        1
+      };
+      for _ in 0..copies {
+        let latency = self
+          .rng
+          .between(self.network.min_latency, self.network.max_latency);
+        // A delivery scheduled past the end of time is delivered at the end of time.
+        self.in_flight.insert(
+          (self.now().saturating_add(latency), self.sent),
+          Delivery {
+            from: process_id,
+            to,
+            key: key.to_owned(),
+            entry: entry.clone(),
+          },
+        );
+        self.sent += 1;
+      }
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,90 @@
+use std::{collections::BTreeMap, ffi::OsString, fs, path::Path};
+
+use crate::{
+  checksum::crc32, Counter, Error, KvEntry, ProcessId, Result, VectorClock, Version, WireFormat,
+};
+
+/// A point-in-time copy of the values of a [`Process`](crate::Process), with their siblings,
+/// clocks and tombstones, and of its vector clock, returned by
+/// [`Process::snapshot`](crate::Process::snapshot).
+///
+/// Saved snapshots start with the CRC-32 checksum of their [`WireFormat`] encoding, as a 32 bit
+/// big endian integer, so a corrupted snapshot fails to load instead of restoring wrong values.
+#[derive(Debug, Clone)]
+pub struct Snapshot<P = usize, C = u64, V = VectorClock<P, C>> {
+  pub(crate) vector_clock: VectorClock<P, C>,
+  pub(crate) entries: BTreeMap<String, KvEntry<P, C, V>>,
+}
+
+impl<P: ProcessId, C: Counter, V: Version<P, C>> Snapshot<P, C, V> {
+  pub(crate) fn new(
+    vector_clock: VectorClock<P, C>,
+    entries: BTreeMap<String, KvEntry<P, C, V>>,
+  ) -> Self {
+    Self {
+      vector_clock,
+      entries,
+    }
+  }
+
+  /// Returns the vector clock of the process when the snapshot was taken.
+  pub fn vector_clock(&self) -> &VectorClock<P, C> {
+    &self.vector_clock
+  }
+
+  /// Returns the entry of every key stored by the process when the snapshot was taken.
+  pub fn entries(&self) -> &BTreeMap<String, KvEntry<P, C, V>> {
+    &self.entries
+  }
+}
+
+impl<P, C, V> Snapshot<P, C, V>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+  V: Version<P, C> + WireFormat,
+{
+  /// Saves the snapshot to `path`, replacing the file atomically and durably: a crash leaves
+  /// either the previous file or the new snapshot, and the new snapshot once this returns.
+  ///
+  /// Fails if the file cannot be written.
+  pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let payload = self.to_bytes();
+    let mut bytes = crc32(&payload).to_be_bytes().to_vec();
+    bytes.extend_from_slice(&payload);
+
+    let mut temporary_path = OsString::from(path);
+    temporary_path.push(".tmp");
+    fs::write(&temporary_path, &bytes)?;
+    fs::File::open(&temporary_path)?.sync_all()?;
+    fs::rename(&temporary_path, path)?;
+
+    // The rename is only durable once the directory holding the snapshot is flushed.
+    let directory = match path.parent() {
+      Some(parent) if !parent.as_os_str().is_empty() => parent,
+      _ => Path::new("."),
+    };
+    fs::File::open(directory)?.sync_all()?;
+    Ok(())
+  }
+
+  /// Loads the snapshot saved at `path` by [`Snapshot::save`].
+  ///
+  /// Fails if the file cannot be read, if it doesn't match its checksum or if it's not a valid
+  /// snapshot.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+    let bytes = fs::read(path)?;
+    let Some((checksum, payload)) = bytes.split_first_chunk::<4>() else {
+      return Err(Error::InvalidEncoding("snapshot is truncated".to_owned()));
+    };
+
+    if crc32(payload) != u32::from_be_bytes(*checksum) {
+      return Err(Error::InvalidEncoding(
+        "snapshot does not match its checksum".to_owned(),
+      ));
+    }
+
+    Self::from_bytes(payload)
+  }
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+
+use crate::{CausalOrdering, Counter, Error, ProcessId, Result};
+
+/// A [vector clock][WikipediaVectorClock] is a data structure used for determining the partial ordering
+/// of events in a distributed system.
+///
+/// [WikipediaVectorClock]: https://en.wikipedia.org/wiki/Vector_clock
+///
+/// Two clocks are equal when they describe the same causal history, regardless of the process that
+/// owns them. Clocks are only partially ordered: concurrent clocks are neither smaller nor greater
+/// than each other.
+///
+/// Processes are identified by any [`ProcessId`] and clocks are sparse: only processes that had
+/// events seen by the clock have an entry, every other process is treated as if its logical clock
+/// was zero. Clocks grow as they learn about new processes.
+///
+/// Logical clocks are stored as a [`Counter`], `u64` by default. `u32` halves the size of each
+/// entry for systems that don't expect as many events per process, and `u8` overflows after a
+/// few events, which is mostly useful to exercise roll-overs.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(
+    try_from = "VectorClockFields<P, C>",
+    bound(
+      deserialize = "P: ProcessId + serde::Deserialize<'de>, C: Counter + serde::Deserialize<'de>"
+    )
+  )
+)]
+pub struct VectorClock<P = usize, C = u64> {
+  /// The id of the process that owns the clock.
+  process_id: P,
+  /// The logical clock of each process that's part of the system.
+  /// Processes without an entry have a logical clock of zero, entries are never zero.
+  clocks: BTreeMap<P, C>,
+}
+
+/// The fields of a [`VectorClock`] as deserialized, before checking that no entry is zero.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct VectorClockFields<P: Ord, C> {
+  process_id: P,
+  clocks: BTreeMap<P, C>,
+}
+
+#[cfg(feature = "serde")]
+impl<P: ProcessId, C: Counter> TryFrom<VectorClockFields<P, C>> for VectorClock<P, C> {
+  type Error = Error;
+
+  fn try_from(fields: VectorClockFields<P, C>) -> Result<Self> {
+    if let Some((process_id, _)) = fields
+      .clocks
+      .iter()
+      .find(|(_, clock)| **clock == C::default())
+    {
+      return Err(Error::InvalidEncoding(format!(
+        "zero entry for process {process_id:?}"
+      )));
+    }
+    Ok(Self {
+      process_id: fields.process_id,
+      clocks: fields.clocks,
+    })
+  }
+}
+
+impl<P: ProcessId, C: Counter> VectorClock<P, C> {
+  /// Creates a clock owned by `process_id` that hasn't seen any events yet.
+  pub fn new(process_id: P) -> Self {
+    Self {
+      process_id,
+      clocks: BTreeMap::new(),
+    }
+  }
+
+  /// Returns the id of the process that owns this clock.
+  pub fn process_id(&self) -> &P {
+    &self.process_id
+  }
+
+  /// Returns the logical clock of every process that had events seen by this clock.
+  pub fn clocks(&self) -> &BTreeMap<P, C> {
+    &self.clocks
+  }
+
+  /// Returns the logical clock of the process `process_id`.
+  pub fn clock(&self, process_id: &P) -> C {
+    self.clocks.get(process_id).copied().unwrap_or_default()
+  }
+
+  /// Records an event that happened in the process that owns this clock.
+  ///
+  /// Fails if the logical clock of the process would overflow.
+  pub fn increment_this_process_clock(&mut self) -> Result<()> {
+    let process_id = self.process_id.clone();
+    self.increment_process_clock(&process_id)
+  }
+
+  /// Records an event that happened in the process `process_id`.
+  ///
+  /// Fails if the logical clock of the process would overflow.
+  pub fn increment_process_clock(&mut self, process_id: &P) -> Result<()> {
+    let clock = self.clock(process_id);
+    let clock = clock
+      .checked_increment()
+      .ok_or_else(|| Error::CounterOverflow(format!("{process_id:?}")))?;
+    self.clocks.insert(process_id.clone(), clock);
+    Ok(())
+  }
+
+  /// Makes `process_id` the owner of the clock, like a process that rolled over to a new
+  /// incarnation.
+  pub(crate) fn set_process_id(&mut self, process_id: P) {
+    self.process_id = process_id;
+  }
+
+  /// Sets the logical clock of the process `process_id` to `clock`.
+  pub(crate) fn set_clock(&mut self, process_id: &P, clock: C) {
+    if clock == C::default() {
+      self.clocks.remove(process_id);
+    } else {
+      self.clocks.insert(process_id.clone(), clock);
+    }
+  }
+
+  /// Forgets every event of the processes for which `keep` returns false. Used to compact clocks
+  /// once processes have been retired from the system.
+  pub fn retain_processes(&mut self, mut keep: impl FnMut(&P) -> bool) {
+    self.clocks.retain(|process_id, _| keep(process_id));
+  }
+
+  /// Records the receipt of a message sent with `other_process_vector_clock`.
+  ///
+  /// Fails if the logical clock of the process that owns this clock would overflow, in which case
+  /// the clock is left untouched.
+  pub fn message_received(&mut self, other_process_vector_clock: &VectorClock<P, C>) -> Result<()> {
+    self.increment_this_process_clock()?;
+    self.join_in_place(other_process_vector_clock);
+    Ok(())
+  }
+
+  /// Returns the least upper bound of both clocks: the smallest clock that has seen every event
+  /// seen by `self` or `other`. Unlike [`VectorClock::message_received`], joining doesn't count
+  /// as an event.
+  pub fn join(&self, other: &VectorClock<P, C>) -> VectorClock<P, C> {
+    let mut clock = self.clone();
+    clock.join_in_place(other);
+    clock
+  }
+
+  /// Same as [`VectorClock::join`] but updates `self` instead of returning a new clock.
+  pub fn join_in_place(&mut self, other: &VectorClock<P, C>) {
+    for (process_id, other_process_value) in other.clocks.iter() {
+      let this_process_value = self.clocks.entry(process_id.clone()).or_default();
+      *this_process_value = std::cmp::max(*this_process_value, *other_process_value);
+    }
+  }
+
+  /// Returns the greatest lower bound of both clocks: the largest clock whose events have been seen
+  /// by both `self` and `other`.
+  pub fn meet(&self, other: &VectorClock<P, C>) -> VectorClock<P, C> {
+    let mut clock = self.clone();
+    clock.meet_in_place(other);
+    clock
+  }
+
+  /// Same as [`VectorClock::meet`] but updates `self` instead of returning a new clock.
+  pub fn meet_in_place(&mut self, other: &VectorClock<P, C>) {
+    // Processes `other` doesn't know about have a logical clock of zero on `other`.
+    self.clocks.retain(|process_id, this_process_value| {
+      *this_process_value = std::cmp::min(*this_process_value, other.clock(process_id));
+      *this_process_value > C::default()
+    });
+  }
+
+  /// Compares the causal histories described by `self` and `other`.
+  pub fn compare(&self, other: &VectorClock<P, C>) -> CausalOrdering {
+    let mut some_smaller = false;
+    let mut some_greater = false;
+
+    for (process_id, this_process_value) in self.clocks.iter() {
+      match this_process_value.cmp(&other.clock(process_id)) {
+        std::cmp::Ordering::Less => some_smaller = true,
+        std::cmp::Ordering::Greater => some_greater = true,
+        std::cmp::Ordering::Equal => {}
+      }
+    }
+
+    // Entries missing from `self` are zero, so any entry only `other` knows about is larger.
+    if other
+      .clocks
+      .keys()
+      .any(|process_id| !self.clocks.contains_key(process_id))
+    {
+      some_smaller = true;
+    }
+
+    match (some_smaller, some_greater) {
+      (false, false) => CausalOrdering::Equal,
+      (true, false) => CausalOrdering::Before,
+      (false, true) => CausalOrdering::After,
+      (true, true) => CausalOrdering::Concurrent,
+    }
+  }
+
+  /// Returns true when the events described by `self` happened before the events described by
+  /// `other`.
+  pub fn happened_before(&self, other: &VectorClock<P, C>) -> bool {
+    self.compare(other) == CausalOrdering::Before
+  }
+
+  /// Returns true when `self` has seen every event `other` has seen, that is, when `other` happened
+  /// before `self` or both clocks are equal.
+  pub fn dominates(&self, other: &VectorClock<P, C>) -> bool {
+    matches!(
+      self.compare(other),
+      CausalOrdering::After | CausalOrdering::Equal
+    )
+  }
+
+  /// Returns true when neither clock has seen the events of the other one.
+  pub fn is_concurrent_with(&self, other: &VectorClock<P, C>) -> bool {
+    self.compare(other) == CausalOrdering::Concurrent
+  }
+}
+
+impl<P: ProcessId, C: Counter> PartialEq for VectorClock<P, C> {
+  fn eq(&self, other: &Self) -> bool {
+    self.compare(other) == CausalOrdering::Equal
+  }
+}
+
+impl<P: ProcessId, C: Counter> PartialOrd for VectorClock<P, C> {
+  fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    self.compare(other).to_partial_ordering()
+  }
+}
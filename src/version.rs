@@ -0,0 +1,57 @@
+use std::fmt::Debug;
+
+use crate::{CausalOrdering, Counter, ProcessId, Result, VectorClock};
+
+/// The causality information a [`KvEntry`](crate::KvEntry) keeps for each of its values.
+///
+/// Causal contexts handed to clients are always vector clocks, but the version of each value can
+/// be tracked with different trade-offs: [`VectorClock`] versions can make concurrent writes
+/// coordinated by the same process look ordered, which
+/// [`DottedVersionVector`](crate::DottedVersionVector) versions avoid.
+/// [`LamportClock`](crate::LamportClock) versions take constant space but order every pair of
+/// values, so concurrent values replicated from other processes are not kept.
+pub trait Version<P, C>: Clone + Debug + PartialEq {
+  /// Returns the version of a write coordinated by `process_id` whose counter is `counter`, from
+  /// a writer that had seen `context`. `counter` is greater than any counter of `process_id` in
+  /// `context`.
+  ///
+  /// Fails if the version would need a counter that overflows.
+  fn for_write(process_id: &P, counter: C, context: &VectorClock<P, C>) -> Result<Self>;
+
+  /// Compares the causal histories described by both versions.
+  fn compare(&self, other: &Self) -> CausalOrdering;
+
+  /// Returns true when a writer that had seen `context` had seen this version.
+  fn is_covered_by(&self, context: &VectorClock<P, C>) -> bool;
+
+  /// Returns the smallest vector clock that covers this version.
+  fn to_vector_clock(&self) -> VectorClock<P, C>;
+
+  /// Forgets every event of the processes for which `keep` returns false.
+  fn retain_processes(&mut self, keep: impl FnMut(&P) -> bool);
+}
+
+impl<P: ProcessId, C: Counter> Version<P, C> for VectorClock<P, C> {
+  fn for_write(process_id: &P, counter: C, context: &VectorClock<P, C>) -> Result<Self> {
+    let mut vector_clock = VectorClock::new(process_id.clone());
+    vector_clock.join_in_place(context);
+    vector_clock.set_clock(process_id, counter);
+    Ok(vector_clock)
+  }
+
+  fn compare(&self, other: &Self) -> CausalOrdering {
+    VectorClock::compare(self, other)
+  }
+
+  fn is_covered_by(&self, context: &VectorClock<P, C>) -> bool {
+    context.dominates(self)
+  }
+
+  fn to_vector_clock(&self) -> VectorClock<P, C> {
+    self.clone()
+  }
+
+  fn retain_processes(&mut self, keep: impl FnMut(&P) -> bool) {
+    VectorClock::retain_processes(self, keep)
+  }
+}
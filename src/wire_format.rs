@@ -0,0 +1,431 @@
+use std::collections::BTreeMap;
+
+use crate::{
+  CausalContext, Counter, Dot, DottedVersionVector, Error, Incarnation, KvEntry, LamportClock,
+  Message, ProcessId, Result, Session, Sibling, Snapshot, VectorClock, Version,
+};
+
+/// The version of the wire format written by this version of the crate.
+pub const WIRE_FORMAT_VERSION: u8 = 1;
+
+/// A stable binary encoding for sending clocks, messages and entries between machines.
+///
+/// [`WireFormat::to_bytes`] writes a single byte with the [`WIRE_FORMAT_VERSION`] followed by the
+/// encoding of the value. [`WireFormat::from_bytes`] decodes any version up to the current one, so
+/// processes can be upgraded one at a time, and rejects newer versions.
+///
+/// Version 1 encodes values as follows:
+///
+/// - Unsigned integers, including process ids and counters, are unsigned LEB128 varints: 7 bits
+///   per byte, least significant group first, with the high bit set on every byte but the last.
+/// - Strings are their length in bytes as a varint followed by their UTF-8 bytes.
+/// - Optional values are a `0` byte for `None`, or a `1` byte followed by the value.
+/// - An [`Incarnation`] is its process id followed by its epoch.
+/// - A [`VectorClock`] is the process id of its owner, its number of entries and every entry as
+///   a process id followed by its counter, in increasing process id order. Processes without an
+///   entry are not encoded, so clocks stay as sparse on the wire as in memory.
+/// - A [`CausalContext`] is an optional vector clock.
+/// - A [`Message`] is its vector clock, its key and its optional value.
+/// - A [`LamportClock`] is its process id followed by its counter.
+/// - A [`DottedVersionVector`] is its optional dot, a process id followed by a counter, followed
+///   by its context.
+/// - A [`Sibling`] is its version, its optional value, the process id of the process that
+///   coordinated the write and its timestamp.
+/// - A [`KvEntry`] is its number of siblings, every sibling, its number of acknowledgments and
+///   every acknowledgment as a process id followed by a vector clock, in increasing process id
+///   order.
+/// - A [`Session`] is its number of keys and every key followed by a vector clock, in increasing
+///   key order.
+/// - A [`Snapshot`] is its vector clock, its number of keys and every key followed by its entry,
+///   in increasing key order.
+pub trait WireFormat: Sized {
+  /// Appends the encoding of the value to `buffer`, without a version.
+  fn encode_into(&self, buffer: &mut Vec<u8>);
+
+  /// Decodes a value from the start of `input` and advances `input` past it.
+  ///
+  /// Fails if `input` doesn't start with a valid encoding.
+  fn decode_from(input: &mut &[u8]) -> Result<Self>;
+
+  /// Returns the encoding of the value, starting with the version of the format.
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut buffer = vec![WIRE_FORMAT_VERSION];
+    self.encode_into(&mut buffer);
+    buffer
+  }
+
+  /// Decodes a value encoded with [`WireFormat::to_bytes`] by this or an older version.
+  ///
+  /// Fails if `bytes` is not a valid encoding, if it has been written by a newer version of the
+  /// format or if there are bytes left after the value.
+  fn from_bytes(bytes: &[u8]) -> Result<Self> {
+    let mut input = bytes;
+    let version = u8::decode_from(&mut input)?;
+    if version == 0 || version > WIRE_FORMAT_VERSION {
+      return Err(Error::InvalidEncoding(format!(
+        "unsupported wire format version {version}"
+      )));
+    }
+
+    let value = Self::decode_from(&mut input)?;
+    if !input.is_empty() {
+      return Err(Error::InvalidEncoding(format!(
+        "{} trailing bytes",
+        input.len()
+      )));
+    }
+    Ok(value)
+  }
+}
+
+fn unexpected_end() -> Error {
+  Error::InvalidEncoding("unexpected end of input".to_owned())
+}
+
+impl WireFormat for u8 {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    buffer.push(*self);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    let (byte, rest) = input.split_first().ok_or_else(unexpected_end)?;
+    *input = rest;
+    Ok(*byte)
+  }
+}
+
+impl WireFormat for u64 {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    let mut value = *self;
+    while value >= 0x80 {
+      buffer.push((value as u8 & 0x7f) | 0x80);
+      value >>= 7;
+    }
+    buffer.push(value as u8);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+      let byte = u8::decode_from(input)?;
+      let bits = (byte & 0x7f) as u64;
+      if shift == 63 && bits > 1 {
+        break;
+      }
+      value |= bits << shift;
+      if byte & 0x80 == 0 {
+        return Ok(value);
+      }
+    }
+    Err(Error::InvalidEncoding(
+      "varint does not fit in 64 bits".to_owned(),
+    ))
+  }
+}
+
+impl WireFormat for u32 {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    (*self as u64).encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    u32::try_from(u64::decode_from(input)?)
+      .map_err(|_| Error::InvalidEncoding("varint does not fit in 32 bits".to_owned()))
+  }
+}
+
+impl WireFormat for usize {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    (*self as u64).encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    usize::try_from(u64::decode_from(input)?)
+      .map_err(|_| Error::InvalidEncoding("varint does not fit in usize".to_owned()))
+  }
+}
+
+impl WireFormat for String {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.len().encode_into(buffer);
+    buffer.extend_from_slice(self.as_bytes());
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    let len = usize::decode_from(input)?;
+    if len > input.len() {
+      return Err(unexpected_end());
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    String::from_utf8(bytes.to_vec())
+      .map_err(|_| Error::InvalidEncoding("string is not valid UTF-8".to_owned()))
+  }
+}
+
+impl<T: WireFormat> WireFormat for Option<T> {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    match self {
+      None => buffer.push(0),
+      Some(value) => {
+        buffer.push(1);
+        value.encode_into(buffer);
+      }
+    }
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    match u8::decode_from(input)? {
+      0 => Ok(None),
+      1 => Ok(Some(T::decode_from(input)?)),
+      tag => Err(Error::InvalidEncoding(format!("invalid option tag {tag}"))),
+    }
+  }
+}
+
+impl<P: WireFormat> WireFormat for Incarnation<P> {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.process_id.encode_into(buffer);
+    self.epoch.encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(Self {
+      process_id: P::decode_from(input)?,
+      epoch: u64::decode_from(input)?,
+    })
+  }
+}
+
+/// Encodes a map as its number of entries followed by every key and value.
+fn encode_map<K: WireFormat, T>(
+  map: &BTreeMap<K, T>,
+  buffer: &mut Vec<u8>,
+  encode_value: impl Fn(&T, &mut Vec<u8>),
+) {
+  map.len().encode_into(buffer);
+  for (key, value) in map.iter() {
+    key.encode_into(buffer);
+    encode_value(value, buffer);
+  }
+}
+
+/// Decodes a map encoded with [`encode_map`], whose keys must be in increasing order.
+fn decode_map<K: WireFormat + Ord, T>(
+  input: &mut &[u8],
+  decode_value: impl Fn(&mut &[u8]) -> Result<T>,
+) -> Result<BTreeMap<K, T>> {
+  let len = usize::decode_from(input)?;
+  let mut map = BTreeMap::new();
+  for _ in 0..len {
+    let key = K::decode_from(input)?;
+    if map.last_key_value().is_some_and(|(last, _)| last >= &key) {
+      return Err(Error::InvalidEncoding(
+        "keys are not in increasing order".to_owned(),
+      ));
+    }
+    map.insert(key, decode_value(input)?);
+  }
+  Ok(map)
+}
+
+impl<P, C> WireFormat for VectorClock<P, C>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.process_id().encode_into(buffer);
+    encode_map(self.clocks(), buffer, C::encode_into);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    let mut vector_clock = VectorClock::new(P::decode_from(input)?);
+    for (process_id, clock) in decode_map::<P, C>(input, C::decode_from)? {
+      if clock == C::default() {
+        return Err(Error::InvalidEncoding(format!(
+          "zero entry for process {process_id:?}"
+        )));
+      }
+      vector_clock.set_clock(&process_id, clock);
+    }
+    Ok(vector_clock)
+  }
+}
+
+impl<P, C> WireFormat for CausalContext<P, C>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    match self.vector_clock() {
+      None => buffer.push(0),
+      Some(vector_clock) => {
+        buffer.push(1);
+        vector_clock.encode_into(buffer);
+      }
+    }
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(match Option::<VectorClock<P, C>>::decode_from(input)? {
+      None => CausalContext::default(),
+      Some(vector_clock) => CausalContext::new(vector_clock),
+    })
+  }
+}
+
+impl<P, C> WireFormat for Message<P, C>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.vector_clock.encode_into(buffer);
+    self.key.encode_into(buffer);
+    self.value.encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(Self {
+      vector_clock: VectorClock::decode_from(input)?,
+      key: String::decode_from(input)?,
+      value: Option::decode_from(input)?,
+    })
+  }
+}
+
+impl<P, C> WireFormat for LamportClock<P, C>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.process_id().encode_into(buffer);
+    self.counter().encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(LamportClock::from_parts(
+      P::decode_from(input)?,
+      C::decode_from(input)?,
+    ))
+  }
+}
+
+impl<P: WireFormat, C: WireFormat> WireFormat for Dot<P, C> {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.process_id.encode_into(buffer);
+    self.counter.encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(Self {
+      process_id: P::decode_from(input)?,
+      counter: C::decode_from(input)?,
+    })
+  }
+}
+
+impl<P, C> WireFormat for DottedVersionVector<P, C>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.dot().cloned().encode_into(buffer);
+    self.context().encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    let dot = Option::<Dot<P, C>>::decode_from(input)?;
+    let context = VectorClock::decode_from(input)?;
+    Ok(match dot {
+      Some(dot) => DottedVersionVector::new(dot, context),
+      None => DottedVersionVector::from_context(context),
+    })
+  }
+}
+
+impl<P: WireFormat, V: WireFormat> WireFormat for Sibling<P, V> {
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.version.encode_into(buffer);
+    self.value.encode_into(buffer);
+    self.process_id.encode_into(buffer);
+    self.timestamp.encode_into(buffer);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(Self {
+      version: V::decode_from(input)?,
+      value: Option::decode_from(input)?,
+      process_id: P::decode_from(input)?,
+      timestamp: u64::decode_from(input)?,
+    })
+  }
+}
+
+impl<P, C, V> WireFormat for KvEntry<P, C, V>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+  V: Version<P, C> + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.siblings().len().encode_into(buffer);
+    for sibling in self.siblings() {
+      sibling.encode_into(buffer);
+    }
+    encode_map(self.acknowledgments(), buffer, VectorClock::encode_into);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    let len = usize::decode_from(input)?;
+    if len == 0 {
+      return Err(Error::InvalidEncoding("entry without siblings".to_owned()));
+    }
+    let mut siblings = Vec::new();
+    for _ in 0..len {
+      siblings.push(Sibling::decode_from(input)?);
+    }
+    let acknowledgments = decode_map(input, VectorClock::decode_from)?;
+    Ok(KvEntry::from_parts(siblings, acknowledgments))
+  }
+}
+
+impl<P, C> WireFormat for Session<P, C>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    encode_map(self.vector_clocks(), buffer, VectorClock::encode_into);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(Session::from_vector_clocks(decode_map(
+      input,
+      VectorClock::decode_from,
+    )?))
+  }
+}
+
+impl<P, C, V> WireFormat for Snapshot<P, C, V>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+  V: Version<P, C> + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    self.vector_clock().encode_into(buffer);
+    encode_map(self.entries(), buffer, KvEntry::encode_into);
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(Snapshot::new(
+      VectorClock::decode_from(input)?,
+      decode_map(input, KvEntry::decode_from)?,
+    ))
+  }
+}
@@ -0,0 +1,363 @@
+use std::{
+  ffi::OsString,
+  fs::{File, OpenOptions},
+  io::{Read, Seek, SeekFrom, Write},
+  path::{Path, PathBuf},
+};
+
+use crate::{
+  checksum::crc32, Counter, Error, KvEntry, ProcessId, Result, Snapshot, VectorClock, Version,
+  WireFormat,
+};
+
+/// The size of the header of each record of a [`WriteAheadLog`]: its length, the checksum of its
+/// length and the checksum of its payload.
+const HEADER_LEN: usize = 12;
+
+/// When a [`WriteAheadLog`] asks the operating system to flush appended records to disk.
+///
+/// Records are always written to the file before the change they describe is applied, so they
+/// survive the process crashing. Only flushed records survive the machine crashing: a process
+/// that loses its latest records may reuse the counters of the writes they described, which
+/// other processes may have seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsyncPolicy {
+  /// Flushes every record before the change it describes is applied.
+  #[default]
+  Always,
+  /// Flushes once every `n` records.
+  Every(usize),
+  /// Leaves flushing to the operating system.
+  Never,
+}
+
+/// When a [`WriteAheadLog`] is compacted: its records are replaced by a [`Snapshot`] of the
+/// process they describe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompactionPolicy {
+  /// Never compacts the log, unless asked to with
+  /// [`Process::compact`](crate::Process::compact).
+  #[default]
+  Never,
+  /// Compacts the log once it's at least this many bytes long.
+  Len(u64),
+  /// Compacts the log once it has at least this many records.
+  Records(usize),
+}
+
+/// A change to the state of a [`Process`](crate::Process), appended to its [`Log`] before it's
+/// applied.
+#[derive(Debug, Clone)]
+pub enum LogRecord<P = usize, C = u64, V = VectorClock<P, C>> {
+  /// `key` now has `entry`, and the vector clock of the process is `vector_clock`.
+  Entry {
+    key: String,
+    entry: KvEntry<P, C, V>,
+    vector_clock: VectorClock<P, C>,
+  },
+  /// The entry of `key` has been removed, once every one of its tombstones was purged.
+  Remove { key: String },
+  /// The process is now identified by `process_id`, the incarnation it rolled over to with
+  /// [`Process::roll_over`](crate::Process::roll_over).
+  ProcessId { process_id: P },
+}
+
+/// Where a [`Process`](crate::Process) records the changes to its values and to its vector clock,
+/// so its state can be restored after a restart with
+/// [`Process::with_log`](crate::Process::with_log).
+pub trait Log<P, C, V> {
+  /// Returns every record appended since the latest snapshot, oldest first.
+  fn records(&mut self) -> Result<Vec<LogRecord<P, C, V>>>;
+
+  /// Appends `record`. The change it describes is only applied if this succeeds.
+  fn append(&mut self, record: &LogRecord<P, C, V>) -> Result<()>;
+
+  /// Returns the snapshot written by the latest compaction, if any.
+  fn latest_snapshot(&mut self) -> Result<Option<Snapshot<P, C, V>>> {
+    Ok(None)
+  }
+
+  /// Returns true when the log has grown enough to be compacted before the next record is
+  /// appended.
+  fn should_compact(&self) -> bool {
+    false
+  }
+
+  /// Replaces every record appended so far with `snapshot`, the state of the process they
+  /// describe.
+  fn compact(&mut self, _snapshot: &Snapshot<P, C, V>) -> Result<()> {
+    Ok(())
+  }
+}
+
+/// A log that keeps nothing: the state of the process is lost when it stops.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Volatile;
+
+impl<P, C, V> Log<P, C, V> for Volatile {
+  fn records(&mut self) -> Result<Vec<LogRecord<P, C, V>>> {
+    Ok(Vec::new())
+  }
+
+  fn append(&mut self, _record: &LogRecord<P, C, V>) -> Result<()> {
+    Ok(())
+  }
+}
+
+/// A log stored in a file.
+///
+/// Each record is its length, the CRC-32 checksum of its length and the CRC-32 checksum of its
+/// [`WireFormat`] encoding, all 32 bit big endian integers, followed by that encoding. A crash
+/// while appending a record may leave it torn at the end of the file, in which case it's dropped
+/// when the log is opened: the change it described was never applied. A corrupted length is
+/// detected by its checksum instead of being mistaken for a torn record.
+///
+/// Compacting the log saves a [`Snapshot`] next to it, at its path followed by `.snapshot`, and
+/// truncates it.
+#[derive(Debug)]
+pub struct WriteAheadLog {
+  file: File,
+  snapshot_path: PathBuf,
+  fsync_policy: FsyncPolicy,
+  compaction_policy: CompactionPolicy,
+  /// How many records have been appended since the file was last flushed.
+  unsynced: usize,
+  /// The length of the file and how many records it holds.
+  len: u64,
+  records: usize,
+}
+
+impl WriteAheadLog {
+  /// Opens the log stored at `path`, creating it if it doesn't exist, and truncates the record
+  /// torn by a crash, if any.
+  ///
+  /// Fails if the file cannot be read or if a record other than the last one is corrupted.
+  pub fn open(path: impl AsRef<Path>, fsync_policy: FsyncPolicy) -> Result<Self> {
+    let path = path.as_ref();
+    let mut file = OpenOptions::new()
+      .read(true)
+      .append(true)
+      .create(true)
+      .open(path)?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let (records, len) = read_records(&bytes)?;
+    if len < bytes.len() {
+      file.set_len(len as u64)?;
+      file.sync_all()?;
+    }
+
+    let mut snapshot_path = OsString::from(path);
+    snapshot_path.push(".snapshot");
+
+    Ok(Self {
+      file,
+      snapshot_path: snapshot_path.into(),
+      fsync_policy,
+      compaction_policy: CompactionPolicy::Never,
+      unsynced: 0,
+      len: len as u64,
+      records: records.len(),
+    })
+  }
+
+  /// Replaces the compaction policy of the log, [`CompactionPolicy::Never`] by default.
+  pub fn with_compaction_policy(self, compaction_policy: CompactionPolicy) -> Self {
+    Self {
+      compaction_policy,
+      ..self
+    }
+  }
+
+  /// Returns when appended records are flushed to disk.
+  pub fn fsync_policy(&self) -> FsyncPolicy {
+    self.fsync_policy
+  }
+
+  /// Returns when the log is compacted.
+  pub fn compaction_policy(&self) -> CompactionPolicy {
+    self.compaction_policy
+  }
+
+  /// Returns the path of the snapshot saved by the latest compaction.
+  pub fn snapshot_path(&self) -> &Path {
+    &self.snapshot_path
+  }
+
+  /// Flushes every appended record to disk, whatever the fsync policy.
+  pub fn sync(&mut self) -> Result<()> {
+    self.file.sync_data()?;
+    self.unsynced = 0;
+    Ok(())
+  }
+}
+
+impl<P, C, V> Log<P, C, V> for WriteAheadLog
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+  V: Version<P, C> + WireFormat,
+{
+  fn records(&mut self) -> Result<Vec<LogRecord<P, C, V>>> {
+    let mut bytes = Vec::new();
+    self.file.seek(SeekFrom::Start(0))?;
+    self.file.read_to_end(&mut bytes)?;
+
+    let (records, _) = read_records(&bytes)?;
+    records.into_iter().map(LogRecord::from_bytes).collect()
+  }
+
+  fn append(&mut self, record: &LogRecord<P, C, V>) -> Result<()> {
+    let payload = record.to_bytes();
+    let len = u32::try_from(payload.len()).map_err(|_| {
+      Error::InvalidEncoding(format!("record of {} bytes is too large", payload.len()))
+    })?;
+
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(&crc32(&len.to_be_bytes()).to_be_bytes());
+    frame.extend_from_slice(&crc32(&payload).to_be_bytes());
+    frame.extend_from_slice(&payload);
+    self.file.write_all(&frame)?;
+
+    self.len += frame.len() as u64;
+    self.records += 1;
+    self.unsynced += 1;
+    let should_sync = match self.fsync_policy {
+      FsyncPolicy::Always => true,
+      FsyncPolicy::Every(records) => self.unsynced >= records,
+      FsyncPolicy::Never => false,
+    };
+    if should_sync {
+      self.sync()?;
+    }
+
+    Ok(())
+  }
+
+  fn latest_snapshot(&mut self) -> Result<Option<Snapshot<P, C, V>>> {
+    if !self.snapshot_path.try_exists()? {
+      return Ok(None);
+    }
+    Snapshot::load(&self.snapshot_path).map(Some)
+  }
+
+  fn should_compact(&self) -> bool {
+    match self.compaction_policy {
+      CompactionPolicy::Never => false,
+      CompactionPolicy::Len(len) => self.len >= len,
+      CompactionPolicy::Records(records) => self.records >= records,
+    }
+  }
+
+  fn compact(&mut self, snapshot: &Snapshot<P, C, V>) -> Result<()> {
+    // Saving the snapshot flushes it and its directory, so it survives a crash once the records
+    // it replaces are truncated.
+    snapshot.save(&self.snapshot_path)?;
+
+    // A crash before the records are truncated replays them on top of the snapshot. Records hold
+    // whole entries, so replaying them in order ends with the state saved in the snapshot.
+    self.file.set_len(0)?;
+    self.file.sync_all()?;
+    self.len = 0;
+    self.records = 0;
+    self.unsynced = 0;
+    Ok(())
+  }
+}
+
+/// Splits `bytes` into the payloads of its records and returns them with the length of the prefix
+/// of `bytes` they take. A torn record at the end of `bytes` is ignored.
+///
+/// Fails if the length of a record doesn't match its checksum, or if a record other than the last
+/// one doesn't match its checksum.
+fn read_records(bytes: &[u8]) -> Result<(Vec<&[u8]>, usize)> {
+  let mut records = Vec::new();
+  let mut offset = 0;
+
+  while let Some(header) = bytes.get(offset..offset + HEADER_LEN) {
+    let len_bytes: [u8; 4] = header[..4].try_into().unwrap();
+    let len_checksum = u32::from_be_bytes(header[4..8].try_into().unwrap());
+    let checksum = u32::from_be_bytes(header[8..].try_into().unwrap());
+    if crc32(&len_bytes) != len_checksum {
+      return Err(Error::InvalidEncoding(format!(
+        "length of the record at offset {offset} does not match its checksum"
+      )));
+    }
+
+    // The length is intact, so a record running past the end of `bytes` is the last one, torn by a
+    // crash while it was being appended.
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    let start = offset + HEADER_LEN;
+    let Some(payload) = bytes.get(start..start + len) else {
+      break;
+    };
+
+    if crc32(payload) != checksum {
+      // The record was being appended when the process crashed if nothing follows it.
+      if start + len == bytes.len() {
+        break;
+      }
+      return Err(Error::InvalidEncoding(format!(
+        "record at offset {offset} does not match its checksum"
+      )));
+    }
+
+    records.push(payload);
+    offset = start + len;
+  }
+
+  Ok((records, offset))
+}
+
+impl<P, C, V> WireFormat for LogRecord<P, C, V>
+where
+  P: ProcessId + WireFormat,
+  C: Counter + WireFormat,
+  V: Version<P, C> + WireFormat,
+{
+  fn encode_into(&self, buffer: &mut Vec<u8>) {
+    match self {
+      LogRecord::Entry {
+        key,
+        entry,
+        vector_clock,
+      } => {
+        buffer.push(0);
+        key.encode_into(buffer);
+        entry.encode_into(buffer);
+        vector_clock.encode_into(buffer);
+      }
+      LogRecord::Remove { key } => {
+        buffer.push(1);
+        key.encode_into(buffer);
+      }
+      LogRecord::ProcessId { process_id } => {
+        buffer.push(2);
+        process_id.encode_into(buffer);
+      }
+    }
+  }
+
+  fn decode_from(input: &mut &[u8]) -> Result<Self> {
+    Ok(match u8::decode_from(input)? {
+      0 => LogRecord::Entry {
+        key: String::decode_from(input)?,
+        entry: KvEntry::decode_from(input)?,
+        vector_clock: VectorClock::decode_from(input)?,
+      },
+      1 => LogRecord::Remove {
+        key: String::decode_from(input)?,
+      },
+      2 => LogRecord::ProcessId {
+        process_id: P::decode_from(input)?,
+      },
+      tag => {
+        return Err(Error::InvalidEncoding(format!(
+          "invalid log record tag {tag}"
+        )))
+      }
+    })
+  }
+}
@@ -0,0 +1,101 @@
+use vector_clocks::{BloomClock, CausalOrdering, Error, Result};
+
+/// Returns a clock of `size` cells and 3 hashes that has seen `events` events of its own.
+fn clock(process_id: usize, size: usize, events: usize) -> Result<BloomClock> {
+  let mut clock = BloomClock::new(process_id, size, 3)?;
+  for _ in 0..events {
+    clock.increment()?;
+  }
+  Ok(clock)
+}
+
+#[test]
+fn clocks_compare_by_their_cells() -> Result<()> {
+  let (mut a, b) = (clock(0, 64, 0)?, clock(1, 64, 0)?);
+  let comparison = a.compare(&b)?;
+  assert_eq!(CausalOrdering::Equal, comparison.ordering);
+  assert_eq!(0.0, comparison.false_positive_probability);
+
+  a.increment()?;
+  assert_eq!(CausalOrdering::After, a.compare(&b)?.ordering);
+  assert_eq!(CausalOrdering::Before, b.compare(&a)?.ordering);
+
+  let mut c = b.clone();
+  c.message_received(&a)?;
+  assert_eq!(CausalOrdering::Before, a.compare(&c)?.ordering);
+  assert_eq!(CausalOrdering::After, c.compare(&a)?.ordering);
+
+  // Events of different processes are hashed into different cells.
+  let d = clock(2, 64, 1)?;
+  let comparison = a.compare(&d)?;
+  assert_eq!(CausalOrdering::Concurrent, comparison.ordering);
+  assert_eq!(0.0, comparison.false_positive_probability);
+  Ok(())
+}
+
+#[test]
+fn clocks_of_different_shapes_are_rejected() -> Result<()> {
+  assert!(matches!(
+    BloomClock::new(0, 0, 3),
+    Err(Error::InvalidConfiguration(_))
+  ));
+  assert!(matches!(
+    BloomClock::new(0, 64, 0),
+    Err(Error::InvalidConfiguration(_))
+  ));
+
+  let mut a = clock(0, 64, 1)?;
+  let b = clock(1, 32, 1)?;
+  assert!(matches!(a.compare(&b), Err(Error::InvalidConfiguration(_))));
+  assert!(matches!(
+    a.message_received(&b),
+    Err(Error::InvalidConfiguration(_))
+  ));
+  assert_eq!(clock(0, 64, 1)?.cells(), a.cells());
+  Ok(())
+}
+
+#[test]
+fn false_positive_estimates_grow_with_unseen_events() -> Result<()> {
+  // The single event of the other clock increments 3 of the 64 cells, each of which covers one
+  // of the 3 cells of an event with probability 1 - (63 / 64)^3.
+  let comparison = clock(0, 64, 0)?.compare(&clock(1, 64, 1)?)?;
+  assert_eq!(CausalOrdering::Before, comparison.ordering);
+  let expected = (1.0 - (63.0f64 / 64.0).powi(3)).powi(3);
+  assert!((comparison.false_positive_probability - expected).abs() < 1e-12);
+
+  let mut previous = 0.0;
+  for events in [1, 10, 100, 1000] {
+    let estimate = clock(0, 64, 0)?
+      .compare(&clock(1, 64, events)?)?
+      .false_positive_probability;
+    assert!(estimate > previous && estimate <= 1.0, "{events} events");
+    previous = estimate;
+  }
+  assert!(previous > 0.99);
+
+  // More cells make a dominating clock less likely to cover an event by chance.
+  let small = clock(0, 64, 0)?.compare(&clock(1, 64, 10)?)?;
+  let large = clock(0, 1024, 0)?.compare(&clock(1, 1024, 10)?)?;
+  assert!(large.false_positive_probability < small.false_positive_probability);
+
+  // The estimate is symmetric in the order of the comparison.
+  let after = clock(1, 64, 10)?.compare(&clock(0, 64, 0)?)?;
+  assert_eq!(CausalOrdering::After, after.ordering);
+  assert_eq!(
+    small.false_positive_probability,
+    after.false_positive_probability
+  );
+  Ok(())
+}
+
+#[test]
+fn events_are_hashed_into_the_same_cells_on_every_build() -> Result<()> {
+  // Processes only agree on what they have seen if they hash the same event into the same cells,
+  // so these must never change.
+  let cells = clock(0, 64, 1)?.cells().to_vec();
+  let incremented: Vec<usize> = (0..cells.len()).filter(|i| cells[*i] == 1).collect();
+  assert_eq!(vec![32, 48, 59], incremented);
+  assert_eq!(3, cells.iter().sum::<u64>());
+  Ok(())
+}
@@ -0,0 +1,92 @@
+use vector_clocks::{Broadcast, CausalBroadcast, Error, Result};
+
+type Layer = CausalBroadcast<usize, u64, &'static str>;
+
+/// Broadcasts `payload` from `sender` and delivers it to `receiver` right away.
+fn send(
+  sender: &mut Layer,
+  receiver: &mut Layer,
+  payload: &'static str,
+) -> Result<Broadcast<usize, u64, &'static str>> {
+  let broadcast = sender.broadcast(payload)?;
+  assert_eq!(vec![payload], receiver.receive(broadcast.clone())?);
+  Ok(broadcast)
+}
+
+#[test]
+fn broadcasts_in_causal_order_are_delivered_right_away() -> Result<()> {
+  let (mut a, mut b) = (Layer::new(0, 4), Layer::new(1, 4));
+  send(&mut a, &mut b, "first")?;
+  send(&mut a, &mut b, "second")?;
+  send(&mut b, &mut a, "reply")?;
+
+  assert!(a.held_back().is_empty());
+  assert_eq!(a.vector_clock(), b.vector_clock());
+  assert_eq!(2, b.vector_clock().clock(&0));
+  assert_eq!(1, b.vector_clock().clock(&1));
+  Ok(())
+}
+
+#[test]
+fn broadcasts_are_held_back_until_their_predecessors_are_delivered() -> Result<()> {
+  let (mut a, mut b, mut c) = (Layer::new(0, 4), Layer::new(1, 4), Layer::new(2, 4));
+
+  // The reply of b depends on the question of a, which reaches c last.
+  let question = send(&mut a, &mut b, "question")?;
+  let reply = b.broadcast("reply")?;
+  assert!(c.receive(reply)?.is_empty());
+  assert_eq!(1, c.held_back().len());
+  assert_eq!(&1, c.held_back()[0].sender());
+  assert_eq!(vec!["question", "reply"], c.receive(question)?);
+  assert!(c.held_back().is_empty());
+
+  // Broadcasts of the same process are delivered in the order they were sent.
+  let first = a.broadcast("first")?;
+  let second = a.broadcast("second")?;
+  let third = a.broadcast("third")?;
+  assert!(c.receive(third)?.is_empty());
+  assert!(c.receive(second)?.is_empty());
+  assert_eq!(vec!["first", "second", "third"], c.receive(first)?);
+  Ok(())
+}
+
+#[test]
+fn duplicate_broadcasts_are_ignored() -> Result<()> {
+  let (mut a, mut b) = (Layer::new(0, 4), Layer::new(1, 4));
+  let first = send(&mut a, &mut b, "first")?;
+  assert!(b.receive(first.clone())?.is_empty());
+
+  let second = a.broadcast("second")?;
+  let third = a.broadcast("third")?;
+  assert!(b.receive(third.clone())?.is_empty());
+  assert!(b.receive(third)?.is_empty());
+  assert_eq!(1, b.held_back().len());
+
+  assert_eq!(vec!["second", "third"], b.receive(second.clone())?);
+  assert!(b.receive(second)?.is_empty());
+  assert!(b.receive(first)?.is_empty());
+  Ok(())
+}
+
+#[test]
+fn broadcasts_past_the_capacity_are_rejected() -> Result<()> {
+  let (mut a, mut b) = (Layer::new(0, 2), Layer::new(1, 2));
+  let first = a.broadcast("first")?;
+  let second = a.broadcast("second")?;
+  let third = a.broadcast("third")?;
+  let fourth = a.broadcast("fourth")?;
+
+  assert!(b.receive(second)?.is_empty());
+  assert!(b.receive(third)?.is_empty());
+  assert!(matches!(
+    b.receive(fourth.clone()),
+    Err(Error::QueueFull(_))
+  ));
+  assert_eq!(2, b.capacity());
+  assert_eq!(2, b.held_back().len());
+
+  // The rejected broadcast is delivered once it's received again.
+  assert_eq!(vec!["first", "second", "third"], b.receive(first)?);
+  assert_eq!(vec!["fourth"], b.receive(fourth)?);
+  Ok(())
+}
@@ -0,0 +1,72 @@
+//! Helpers shared by the integration tests, included as a module by each of them. Every test crate
+//! that includes it uses only some of the helpers.
+#![allow(dead_code)]
+
+use std::{
+  collections::BTreeMap,
+  fs,
+  io::ErrorKind,
+  path::{Path, PathBuf},
+};
+
+use vector_clocks::{
+  CausalContext, DottedVersionVector, FsyncPolicy, KeepSiblings, KvEntry, Process, Result,
+  SystemClock, VectorClock, WriteAheadLog,
+};
+
+/// A process whose values are versioned with dotted version vectors.
+pub type DvvProcess = Process<usize, u64, KeepSiblings, DottedVersionVector>;
+
+/// A process whose values and vector clock are kept in a [`WriteAheadLog`].
+pub type LoggedProcess =
+  Process<usize, u64, KeepSiblings, DottedVersionVector, SystemClock, WriteAheadLog>;
+
+/// The values and the vector clock of a process.
+pub type State = (
+  VectorClock,
+  BTreeMap<String, KvEntry<usize, u64, DottedVersionVector>>,
+);
+
+/// Returns a path in the temporary directory that's unique to the test, removing the log and the
+/// snapshot left there by an earlier run.
+pub fn log_path(name: &str) -> PathBuf {
+  let path = std::env::temp_dir().join(format!("vector_clocks_{}_{name}.log", std::process::id()));
+  remove_log(&path).unwrap();
+  path
+}
+
+/// Removes the log stored at `path` and its snapshot, if they exist.
+pub fn remove_log(path: &Path) -> Result<()> {
+  let mut snapshot_path = path.as_os_str().to_owned();
+  snapshot_path.push(".snapshot");
+  remove_file(path)?;
+  remove_file(Path::new(&snapshot_path))
+}
+
+fn remove_file(path: &Path) -> Result<()> {
+  match fs::remove_file(path) {
+    Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+    _ => Ok(()),
+  }
+}
+
+pub fn open(path: &Path, fsync_policy: FsyncPolicy) -> Result<LoggedProcess> {
+  Process::with_members(0, [0, 1]).with_log(WriteAheadLog::open(path, fsync_policy)?)
+}
+
+pub fn state(process: &LoggedProcess) -> State {
+  let entries = process
+    .keys()
+    .map(|key| (key.clone(), process.entry(key).unwrap().clone()))
+    .collect();
+  (process.vector_clock().clone(), entries)
+}
+
+/// Asserts that the next write of a restarted process is counted after every write it made before
+/// the restart.
+pub fn assert_counts_past_the_restart(process: &mut LoggedProcess) -> Result<()> {
+  let counter = process.vector_clock().clock(&0);
+  process.put("key", "after restart".to_owned(), CausalContext::default())?;
+  assert_eq!(counter + 1, process.vector_clock().clock(&0));
+  Ok(())
+}
@@ -0,0 +1,121 @@
+use vector_clocks::{
+  CausalContext, ConflictResolver, HighestProcessIdWins, KeepSiblings, LastWriterWins, MergeWith,
+  PhysicalClock, Process, Result, VectorClock,
+};
+
+/// A physical clock stuck at the same time.
+struct FixedClock(u64);
+
+impl PhysicalClock for FixedClock {
+  fn now(&self) -> u64 {
+    self.0
+  }
+}
+
+type ResolvingProcess<R> = Process<usize, u64, R, VectorClock, FixedClock>;
+
+/// Returns process `process_id` of a system of processes 0 and 1, resolving conflicts with
+/// `resolver` and timestamping values with `now`.
+fn process<R: ConflictResolver<usize, u64, VectorClock>>(
+  process_id: usize,
+  resolver: R,
+  now: u64,
+) -> ResolvingProcess<R> {
+  Process::with_members(process_id, [0, 1])
+    .with_resolver(resolver)
+    .with_physical_clock(FixedClock(now))
+}
+
+/// Writes `value` to "key" through `writer` and merges the write into `reader`, which has
+/// written a concurrent value.
+fn merge_concurrent_write<R, R2>(
+  reader: &mut ResolvingProcess<R>,
+  writer: &mut ResolvingProcess<R2>,
+  value: &str,
+) -> Result<()>
+where
+  R: ConflictResolver<usize, u64, VectorClock>,
+  R2: ConflictResolver<usize, u64, VectorClock>,
+{
+  writer.put("key", value.to_owned(), CausalContext::default())?;
+  reader.merge_entry("key", writer.entry("key")?)
+}
+
+#[test]
+fn keep_siblings_keeps_every_concurrent_value() -> Result<()> {
+  let mut a = process(0, KeepSiblings, 200);
+  a.put("key", "from 0".to_owned(), CausalContext::default())?;
+  merge_concurrent_write(&mut a, &mut process(1, KeepSiblings, 100), "from 1")?;
+  assert_eq!(
+    vec!["from 0".to_owned(), "from 1".to_owned()],
+    a.get("key")?.values
+  );
+  Ok(())
+}
+
+#[test]
+fn last_writer_wins_keeps_the_latest_timestamp() -> Result<()> {
+  let mut a = process(0, LastWriterWins, 100);
+  a.put("key", "earlier".to_owned(), CausalContext::default())?;
+  merge_concurrent_write(&mut a, &mut process(1, LastWriterWins, 200), "later")?;
+  assert_eq!(vec!["later".to_owned()], a.get("key")?.values);
+  assert!(!a.entry("key")?.has_siblings());
+
+  // A later local write wins over an earlier remote one.
+  let mut b = process(0, LastWriterWins, 300);
+  b.put("key", "later".to_owned(), CausalContext::default())?;
+  merge_concurrent_write(&mut b, &mut process(1, LastWriterWins, 200), "earlier")?;
+  assert_eq!(vec!["later".to_owned()], b.get("key")?.values);
+
+  // Ties are broken by process id.
+  let mut c = process(0, LastWriterWins, 100);
+  c.put("key", "from 0".to_owned(), CausalContext::default())?;
+  merge_concurrent_write(&mut c, &mut process(1, LastWriterWins, 100), "from 1")?;
+  assert_eq!(vec!["from 1".to_owned()], c.get("key")?.values);
+  Ok(())
+}
+
+#[test]
+fn highest_process_id_wins_ignores_timestamps() -> Result<()> {
+  let mut a = process(0, HighestProcessIdWins, 200);
+  a.put("key", "from 0".to_owned(), CausalContext::default())?;
+  merge_concurrent_write(&mut a, &mut process(1, HighestProcessIdWins, 100), "from 1")?;
+  assert_eq!(vec!["from 1".to_owned()], a.get("key")?.values);
+
+  // Every process reaches the same decision.
+  let mut b = process(1, HighestProcessIdWins, 100);
+  b.put("key", "from 1".to_owned(), CausalContext::default())?;
+  merge_concurrent_write(&mut b, &mut process(0, HighestProcessIdWins, 200), "from 0")?;
+  assert_eq!(vec!["from 1".to_owned()], b.get("key")?.values);
+  Ok(())
+}
+
+#[test]
+fn merge_with_replaces_concurrent_values_with_the_merged_value() -> Result<()> {
+  let union = |_: &str, values: &[&String]| {
+    let mut values: Vec<&str> = values.iter().map(|value| value.as_str()).collect();
+    values.sort();
+    values.join(",")
+  };
+
+  let mut a = process(0, MergeWith(union), 100);
+  a.put("key", "b".to_owned(), CausalContext::default())?;
+  merge_concurrent_write(&mut a, &mut process(1, KeepSiblings, 100), "a")?;
+  assert_eq!(vec!["a,b".to_owned()], a.get("key")?.values);
+  assert!(!a.entry("key")?.has_siblings());
+
+  // The merged value is written by the resolving process and supersedes both inputs.
+  let mut b = process(1, KeepSiblings, 100);
+  b.put("key", "a".to_owned(), CausalContext::default())?;
+  b.merge_entry("key", a.entry("key")?)?;
+  assert_eq!(vec!["a,b".to_owned()], b.get("key")?.values);
+
+  // A delete concurrent with a value is superseded by the merge of the values.
+  let mut c = process(0, MergeWith(union), 100);
+  let context = c.put("key", "deleted".to_owned(), CausalContext::default())?;
+  c.delete("key", context)?;
+  merge_concurrent_write(&mut c, &mut process(1, KeepSiblings, 100), "kept")?;
+  assert_eq!(vec!["kept".to_owned()], c.get("key")?.values);
+  assert!(!c.entry("key")?.has_siblings());
+  Ok(())
+}
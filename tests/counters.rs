@@ -0,0 +1,58 @@
+use vector_clocks::{
+  CausalContext, Error, Incarnation, Process, Result, VectorClock, WireFormat, WIRE_FORMAT_VERSION,
+};
+
+type SmallProcess = Process<Incarnation<usize>, u8>;
+
+#[test]
+fn writes_fail_at_the_last_counter_until_the_process_rolls_over() -> Result<()> {
+  let first = Incarnation::first(0);
+  let mut process: SmallProcess = Process::with_members(first, [first]);
+  for i in 0..u8::MAX {
+    process.put("key", format!("value {i}"), CausalContext::default())?;
+  }
+  assert_eq!(u8::MAX, process.vector_clock().clock(&first));
+
+  assert_eq!(
+    Err(Error::CounterOverflow(format!("{first:?}"))),
+    process.put("key", "overflow".to_owned(), CausalContext::default())
+  );
+  assert_eq!(u8::MAX as usize, process.get("key")?.values.len());
+
+  process.roll_over()?;
+  let next = first.next();
+  assert_eq!(&next, process.process_id());
+  let context = process.get("key")?.context;
+  process.put("key", "after roll over".to_owned(), context)?;
+  assert_eq!(
+    vec!["after roll over".to_owned()],
+    process.get("key")?.values
+  );
+
+  // The previous incarnation stays a member and its events stay in the clock.
+  assert!(process.membership().is_member(&first));
+  assert!(process.membership().is_member(&next));
+  assert_eq!(u8::MAX, process.vector_clock().clock(&first));
+  assert_eq!(1, process.vector_clock().clock(&next));
+  Ok(())
+}
+
+#[test]
+fn u32_counters_overflow_at_their_maximum() -> Result<()> {
+  // The owner of the clock and its number of entries, followed by its single entry.
+  let mut bytes = vec![WIRE_FORMAT_VERSION];
+  0usize.encode_into(&mut bytes);
+  1usize.encode_into(&mut bytes);
+  0usize.encode_into(&mut bytes);
+  u32::MAX.encode_into(&mut bytes);
+  let mut clock = VectorClock::<usize, u32>::from_bytes(&bytes)?;
+
+  assert_eq!(
+    Err(Error::CounterOverflow("0".to_owned())),
+    clock.increment_this_process_clock()
+  );
+  assert_eq!(u32::MAX, clock.clock(&0));
+  clock.increment_process_clock(&1)?;
+  assert_eq!(1, clock.clock(&1));
+  Ok(())
+}
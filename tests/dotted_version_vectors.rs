@@ -0,0 +1,175 @@
+mod common;
+
+use common::DvvProcess;
+use vector_clocks::{
+  CausalContext, Dot, DottedVersionVector, DottedVersionVectorSet, Process, Result, VectorClock,
+};
+
+/// A key stored with a single vector clock for all of its values, like Riak did before dotted
+/// version vectors: a write either replaces every value or is added as one more sibling.
+struct SingleClockObject {
+  vector_clock: VectorClock,
+  values: Vec<String>,
+}
+
+impl SingleClockObject {
+  fn put(&mut self, context: &VectorClock, value: String) -> Result<VectorClock> {
+    if context.dominates(&self.vector_clock) {
+      self.values = vec![value];
+    } else {
+      self.values.push(value);
+    }
+    self.vector_clock.join_in_place(context);
+    self.vector_clock.increment_this_process_clock()?;
+    Ok(self.vector_clock.clone())
+  }
+}
+
+#[test]
+fn single_clock_objects_explode_while_dotted_version_vector_sets_stay_bounded() -> Result<()> {
+  let mut object = SingleClockObject {
+    vector_clock: VectorClock::new(0),
+    values: Vec::new(),
+  };
+  let mut set: DottedVersionVectorSet = DottedVersionVectorSet::new(0);
+
+  // Two clients keep writing through the same server, each with the context of its own last write.
+  let mut object_contexts = [VectorClock::new(0), VectorClock::new(0)];
+  let mut set_contexts = [VectorClock::new(0), VectorClock::new(0)];
+  for round in 0..10 {
+    for client in 0..2 {
+      let value = format!("client {client} round {round}");
+      object_contexts[client] = object.put(&object_contexts[client], value.clone())?;
+      set.update(&set_contexts[client], value)?;
+      set_contexts[client] = set.join();
+    }
+  }
+
+  assert_eq!(20, object.values.len());
+  assert_eq!(
+    vec!["client 1 round 9", "client 0 round 9"],
+    set.values().collect::<Vec<_>>()
+  );
+
+  Ok(())
+}
+
+#[test]
+fn dotted_version_vector_sets_sync_concurrent_values() -> Result<()> {
+  let mut set_0: DottedVersionVectorSet = DottedVersionVectorSet::new(0);
+  let mut set_1: DottedVersionVectorSet = DottedVersionVectorSet::new(1);
+
+  set_0.update(&VectorClock::new(0), "value 1".to_owned())?;
+  set_1.sync(&set_0);
+  set_1.update(&set_1.join(), "value 2".to_owned())?;
+  set_0.update(&set_0.join(), "value 3".to_owned())?;
+
+  set_0.sync(&set_1);
+  set_1.sync(&set_0);
+
+  assert_eq!(
+    vec!["value 3", "value 2"],
+    set_0.values().collect::<Vec<_>>()
+  );
+  assert_eq!(
+    set_0.values().collect::<Vec<_>>(),
+    set_1.values().collect::<Vec<_>>()
+  );
+
+  Ok(())
+}
+
+#[test]
+fn dots_tell_apart_writes_that_vector_clocks_order() {
+  let mut first_vector_clock: VectorClock = VectorClock::new(0);
+  first_vector_clock.increment_this_process_clock().unwrap();
+  let mut second_vector_clock = first_vector_clock.clone();
+  second_vector_clock.increment_this_process_clock().unwrap();
+  assert!(first_vector_clock.happened_before(&second_vector_clock));
+
+  // Both writes were coordinated by process 0 for clients that hadn't read anything.
+  let first: DottedVersionVector = DottedVersionVector::new(
+    Dot {
+      process_id: 0,
+      counter: 1,
+    },
+    VectorClock::new(0),
+  );
+  let second: DottedVersionVector = DottedVersionVector::new(
+    Dot {
+      process_id: 0,
+      counter: 2,
+    },
+    VectorClock::new(0),
+  );
+  assert!(first.is_concurrent_with(&second));
+
+  // A client that had read the first write supersedes it.
+  let third: DottedVersionVector = DottedVersionVector::new(
+    Dot {
+      process_id: 0,
+      counter: 3,
+    },
+    first_vector_clock,
+  );
+  assert!(first.happened_before(&third));
+  assert!(second.is_concurrent_with(&third));
+  assert!(third.join(&second).dominates(&first));
+}
+
+#[test]
+fn merging_entries_keeps_concurrent_writes_through_the_same_process() -> Result<()> {
+  let mut vector_clock_process_0: Process = Process::with_members(0, [1]);
+  let mut vector_clock_process_1: Process = Process::with_members(1, [0]);
+  let mut dvv_process_0: DvvProcess = Process::with_members(0, [1]);
+  let mut dvv_process_1: DvvProcess = Process::with_members(1, [0]);
+
+  for value in ["value 1", "value 2"] {
+    vector_clock_process_0.put("key", value.to_owned(), CausalContext::default())?;
+    dvv_process_0.put("key", value.to_owned(), CausalContext::default())?;
+  }
+  assert_eq!(2, vector_clock_process_0.get("key")?.values.len());
+  assert_eq!(2, dvv_process_0.get("key")?.values.len());
+
+  vector_clock_process_1.merge_entry("key", vector_clock_process_0.entry("key")?)?;
+  dvv_process_1.merge_entry("key", dvv_process_0.entry("key")?)?;
+
+  // The vector clock of the second write dominates the first one, which is lost.
+  assert_eq!(vec!["value 2"], vector_clock_process_1.get("key")?.values);
+  assert_eq!(vec!["value 1", "value 2"], dvv_process_1.get("key")?.values);
+
+  Ok(())
+}
+
+#[test]
+fn siblings_stay_bounded_with_clients_writing_through_different_processes() -> Result<()> {
+  let mut process_0: DvvProcess = Process::with_members(0, [1]);
+  let mut process_1: DvvProcess = Process::with_members(1, [0]);
+
+  process_0.put("key", "initial".to_owned(), CausalContext::default())?;
+  process_1.merge_entry("key", process_0.entry("key")?)?;
+
+  for round in 0..10 {
+    // Both clients read before either of them writes, so each write is concurrent with the other.
+    let context_0 = process_0.get("key")?.context;
+    let context_1 = process_1.get("key")?.context;
+    process_0.put("key", format!("client 0 round {round}"), context_0)?;
+    process_1.put("key", format!("client 1 round {round}"), context_1)?;
+
+    process_0.merge_entry("key", process_1.entry("key")?)?;
+    process_1.merge_entry("key", process_0.entry("key")?)?;
+
+    let expected = vec![
+      format!("client 0 round {round}"),
+      format!("client 1 round {round}"),
+    ];
+    let mut values_0 = process_0.get("key")?.values;
+    let mut values_1 = process_1.get("key")?.values;
+    values_0.sort();
+    values_1.sort();
+    assert_eq!(expected, values_0);
+    assert_eq!(expected, values_1);
+  }
+
+  Ok(())
+}
@@ -0,0 +1,110 @@
+use vector_clocks::{
+  CausalContext, Error, Message, Process, Result, VectorClock, WireFormat, WIRE_FORMAT_VERSION,
+};
+
+/// Returns a clock of process 0 that has seen every event its counter can count.
+fn exhausted_clock() -> Result<VectorClock> {
+  // The owner of the clock and its number of entries, followed by its single entry.
+  let mut bytes = vec![WIRE_FORMAT_VERSION];
+  0usize.encode_into(&mut bytes);
+  1usize.encode_into(&mut bytes);
+  0usize.encode_into(&mut bytes);
+  u64::MAX.encode_into(&mut bytes);
+  VectorClock::from_bytes(&bytes)
+}
+
+#[test]
+fn missing_keys_are_unknown() -> Result<()> {
+  let mut process: Process = Process::with_members(0, [0, 1]);
+  let unknown_key = Some(Error::UnknownKey("key".to_owned()));
+  assert_eq!(unknown_key, process.get("key").err());
+  assert_eq!(unknown_key, process.entry("key").err());
+
+  let context = process.put("key", "value".to_owned(), CausalContext::default())?;
+  process.delete("key", context)?;
+  assert_eq!(unknown_key, process.get("key").err());
+  assert_eq!(
+    "key \"key\" not found",
+    Error::UnknownKey("key".to_owned()).to_string()
+  );
+  Ok(())
+}
+
+#[test]
+fn exhausted_counters_overflow() -> Result<()> {
+  let mut clock = exhausted_clock()?;
+  assert_eq!(
+    Err(Error::CounterOverflow("0".to_owned())),
+    clock.increment_this_process_clock()
+  );
+  assert_eq!(u64::MAX, clock.clock(&0));
+
+  let mut process: Process = Process::with_members(0, [0, 1]);
+  assert_eq!(
+    Err(Error::CounterOverflow("0".to_owned())),
+    process.receive_message(Message {
+      vector_clock: exhausted_clock()?,
+      key: "key".to_owned(),
+      value: Some("value".to_owned()),
+    })
+  );
+  assert_eq!(0, process.vector_clock().clock(&0));
+  Ok(())
+}
+
+#[test]
+fn membership_changes_are_checked() -> Result<()> {
+  let mut process: Process = Process::with_members(0, [0, 1, 2]);
+  assert_eq!(
+    Err(Error::DuplicateProcess("1".to_owned())),
+    process.add_process(1)
+  );
+  assert_eq!(
+    Err(Error::UnknownProcess("3".to_owned())),
+    process.retire_process(&3)
+  );
+  assert_eq!(
+    Err(Error::InvalidProcessId("0".to_owned())),
+    process.retire_process(&0)
+  );
+  assert_eq!(
+    Err(Error::UnknownProcess("3".to_owned())),
+    process.acknowledge(&3, "key", &VectorClock::new(3))
+  );
+
+  process.retire_process(&2)?;
+  assert_eq!(
+    Err(Error::RetiredProcess("2".to_owned())),
+    process.add_process(2)
+  );
+  assert_eq!(
+    Err(Error::UnknownProcess("2".to_owned())),
+    process.retire_process(&2)
+  );
+  Ok(())
+}
+
+#[test]
+fn invalid_encodings_are_rejected() -> Result<()> {
+  let unexpected_end = Err(Error::InvalidEncoding("unexpected end of input".to_owned()));
+  assert_eq!(unexpected_end, VectorClock::<usize, u64>::from_bytes(&[]));
+  assert_eq!(
+    unexpected_end,
+    VectorClock::<usize, u64>::from_bytes(&[WIRE_FORMAT_VERSION])
+  );
+
+  assert_eq!(
+    Err(Error::InvalidEncoding(
+      "unsupported wire format version 255".to_owned()
+    )),
+    VectorClock::<usize, u64>::from_bytes(&[255, 0, 0])
+  );
+
+  let mut bytes = VectorClock::<usize, u64>::new(0).to_bytes();
+  bytes.push(0);
+  assert_eq!(
+    Err(Error::InvalidEncoding("1 trailing bytes".to_owned())),
+    VectorClock::<usize, u64>::from_bytes(&bytes)
+  );
+  Ok(())
+}
@@ -0,0 +1,90 @@
+use std::{cell::Cell, rc::Rc};
+
+use vector_clocks::{Error, HybridLogicalClock, HybridTimestamp, PhysicalClock, Result};
+
+/// A physical clock that only moves when the test moves it.
+#[derive(Clone, Default)]
+struct ManualClock(Rc<Cell<u64>>);
+
+impl ManualClock {
+  fn set(&self, now: u64) {
+    self.0.set(now);
+  }
+}
+
+impl PhysicalClock for ManualClock {
+  fn now(&self) -> u64 {
+    self.0.get()
+  }
+}
+
+fn timestamp(physical: u64, logical: u32) -> HybridTimestamp {
+  HybridTimestamp { physical, logical }
+}
+
+#[test]
+fn local_events_follow_the_physical_clock() -> Result<()> {
+  let physical_clock = ManualClock::default();
+  let mut clock = HybridLogicalClock::new(physical_clock.clone(), 1_000);
+
+  physical_clock.set(100);
+  assert_eq!(timestamp(100, 0), clock.local_event()?);
+  assert_eq!(timestamp(100, 1), clock.send()?);
+
+  // The physical clock going backwards doesn't make timestamps go backwards.
+  physical_clock.set(90);
+  assert_eq!(timestamp(100, 2), clock.local_event()?);
+
+  physical_clock.set(110);
+  assert_eq!(timestamp(110, 0), clock.local_event()?);
+
+  Ok(())
+}
+
+#[test]
+fn received_timestamps_are_greater_than_the_sent_ones() -> Result<()> {
+  let sender_physical_clock = ManualClock::default();
+  let receiver_physical_clock = ManualClock::default();
+  let mut sender = HybridLogicalClock::new(sender_physical_clock.clone(), 1_000);
+  let mut receiver = HybridLogicalClock::new(receiver_physical_clock.clone(), 1_000);
+
+  sender_physical_clock.set(500);
+  receiver_physical_clock.set(200);
+  let sent = sender.send()?;
+  let received = receiver.receive(&sent)?;
+  assert_eq!(timestamp(500, 1), received);
+  assert!(sent < received);
+
+  // Once the physical clock catches up, the logical counter starts over.
+  receiver_physical_clock.set(501);
+  assert_eq!(timestamp(501, 0), receiver.local_event()?);
+
+  Ok(())
+}
+
+#[test]
+fn timestamps_too_far_in_the_future_are_rejected() -> Result<()> {
+  let physical_clock = ManualClock::default();
+  let mut clock = HybridLogicalClock::new(physical_clock.clone(), 1_000);
+
+  physical_clock.set(10_000);
+  let latest = clock.local_event()?;
+
+  assert!(matches!(
+    clock.receive(&timestamp(11_001, 0)),
+    Err(Error::ClockDrift(_))
+  ));
+  assert_eq!(latest, clock.latest());
+
+  assert_eq!(timestamp(11_000, 1), clock.receive(&timestamp(11_000, 0))?);
+
+  Ok(())
+}
+
+#[test]
+fn closures_can_be_used_as_physical_clocks() -> Result<()> {
+  let mut clock = HybridLogicalClock::new(|| 42, 0);
+  assert_eq!(timestamp(42, 0), clock.local_event()?);
+  assert_eq!(timestamp(42, 1), clock.local_event()?);
+  Ok(())
+}
@@ -0,0 +1,186 @@
+use vector_clocks::{CausalOrdering, Error, IntervalTreeClock, Result, Rng, VectorClock};
+
+/// A replica tracked both by an interval tree clock and by a vector clock keyed by a name that's
+/// never reused.
+struct Replica {
+  stamp: IntervalTreeClock,
+  vector_clock: VectorClock,
+}
+
+/// Runs a random history of events, forks, joins and messages, and returns a snapshot of every
+/// replica after each step.
+fn random_history(seed: u64, steps: usize) -> Result<Vec<(IntervalTreeClock, VectorClock)>> {
+  let mut rng = Rng::new(seed);
+  let mut next_name = 1;
+  let mut replicas = vec![Replica {
+    stamp: IntervalTreeClock::seed(),
+    vector_clock: VectorClock::new(0),
+  }];
+  let mut snapshots = Vec::new();
+
+  for _ in 0..steps {
+    let i = rng.below(replicas.len());
+    match rng.below(4) {
+      0 => {
+        replicas[i].stamp.event()?;
+        replicas[i].vector_clock.increment_this_process_clock()?;
+      }
+      1 if replicas.len() < 8 => {
+        let replica = replicas.swap_remove(i);
+        let (left, right) = replica.stamp.fork();
+        let mut vector_clock = VectorClock::new(next_name);
+        vector_clock.join_in_place(&replica.vector_clock);
+        next_name += 1;
+        replicas.push(Replica {
+          stamp: left,
+          vector_clock: replica.vector_clock,
+        });
+        replicas.push(Replica {
+          stamp: right,
+          vector_clock,
+        });
+      }
+      2 if replicas.len() > 1 => {
+        let other = replicas.swap_remove(i);
+        let j = rng.below(replicas.len());
+        let stamp = replicas[j].stamp.clone().join(other.stamp)?;
+        replicas[j].stamp = stamp;
+        replicas[j].vector_clock.join_in_place(&other.vector_clock);
+      }
+      _ => {
+        let j = rng.below(replicas.len());
+        let message = replicas[j].stamp.peek();
+        let message_vector_clock = replicas[j].vector_clock.clone();
+        replicas[i].stamp.message_received(&message)?;
+        replicas[i]
+          .vector_clock
+          .message_received(&message_vector_clock)?;
+      }
+    }
+
+    for replica in replicas.iter() {
+      snapshots.push((replica.stamp.peek(), replica.vector_clock.clone()));
+    }
+  }
+
+  Ok(snapshots)
+}
+
+#[test]
+fn ordering_agrees_with_vector_clocks() -> Result<()> {
+  for seed in 1..=50 {
+    let snapshots = random_history(seed, 60)?;
+    for (stamp, vector_clock) in snapshots.iter() {
+      for (other_stamp, other_vector_clock) in snapshots.iter() {
+        assert_eq!(
+          vector_clock.compare(other_vector_clock),
+          stamp.compare(other_stamp),
+          "seed {seed}: {stamp:?} vs {other_stamp:?}"
+        );
+      }
+    }
+  }
+
+  Ok(())
+}
+
+#[test]
+fn encoding_round_trips() -> Result<()> {
+  for seed in 1..=50 {
+    for (stamp, _) in random_history(seed, 60)? {
+      let bytes = stamp.encode();
+      let decoded = IntervalTreeClock::decode(&bytes)?;
+      assert_eq!(CausalOrdering::Equal, stamp.compare(&decoded));
+      assert_eq!(bytes, decoded.encode());
+    }
+  }
+
+  Ok(())
+}
+
+#[test]
+fn joining_every_fork_gives_back_the_whole_interval() -> Result<()> {
+  let mut rng = Rng::new(42);
+  let mut stamps = vec![IntervalTreeClock::seed()];
+  for _ in 0..20 {
+    let stamp = stamps.swap_remove(rng.below(stamps.len()));
+    let (left, right) = stamp.fork();
+    stamps.push(left);
+    stamps.push(right);
+    let i = rng.below(stamps.len());
+    stamps[i].event()?;
+  }
+
+  let mut stamp = stamps.pop().unwrap();
+  while let Some(other) = stamps.pop() {
+    stamp = stamp.join(other)?;
+  }
+  stamp.event()?;
+
+  // Once the whole interval is owned again, the stamp normalises back to a seed with events.
+  let mut seed = IntervalTreeClock::seed();
+  let mut encodings = vec![seed.encode()];
+  for _ in 0..100 {
+    seed.event()?;
+    encodings.push(seed.encode());
+  }
+  assert!(encodings.contains(&stamp.encode()));
+
+  Ok(())
+}
+
+#[test]
+fn invalid_operations_are_rejected() {
+  let seed = IntervalTreeClock::seed();
+  let (left, _) = seed.fork();
+
+  assert!(matches!(
+    left.clone().join(left.clone()),
+    Err(Error::InvalidStamp(_))
+  ));
+  assert!(matches!(left.peek().event(), Err(Error::InvalidStamp(_))));
+  assert!(matches!(
+    IntervalTreeClock::decode(&[]),
+    Err(Error::InvalidEncoding(_))
+  ));
+  assert!(matches!(
+    IntervalTreeClock::decode(&[0, 0, 0]),
+    Err(Error::InvalidEncoding(_))
+  ));
+}
+
+/// Packs `bits`, a string of zeros and ones, into bytes padded with zeros.
+fn pack(bits: &str) -> Vec<u8> {
+  bits
+    .as_bytes()
+    .chunks(8)
+    .map(|chunk| {
+      chunk
+        .iter()
+        .enumerate()
+        .fold(0, |byte, (i, bit)| byte | ((bit - b'0') << (7 - i)))
+    })
+    .collect()
+}
+
+#[test]
+fn deeply_nested_encodings_are_rejected() -> Result<()> {
+  // An id made of `depth` nodes whose left half is anonymous, and an event tree made of `depth`
+  // nodes whose left half is zero, each ending with a leaf.
+  let id = |depth: usize| format!("{}001{}", "01".repeat(depth), "1000");
+  let event = |depth: usize| format!("001{}{}", "000".repeat(depth), "1000");
+
+  for encode in [id, event] {
+    IntervalTreeClock::decode(&pack(&encode(128)))?;
+    assert!(matches!(
+      IntervalTreeClock::decode(&pack(&encode(129))),
+      Err(Error::InvalidEncoding(_))
+    ));
+    // Deep enough to overflow the stack of a decoder without a limit.
+    assert!(matches!(
+      IntervalTreeClock::decode(&pack(&encode(1_000_000))),
+      Err(Error::InvalidEncoding(_))
+    ));
+  }
+  Ok(())
+}
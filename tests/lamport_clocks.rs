@@ -0,0 +1,154 @@
+use vector_clocks::{
+  CausalContext, CausalOrdering, Error, KeepSiblings, LamportClock, LogicalClock, Message, Process,
+  Result, VectorClock, WireFormat, WIRE_FORMAT_VERSION,
+};
+
+type LamportProcess = Process<usize, u64, KeepSiblings, LamportClock>;
+
+/// Returns a clock of process 1 that has seen `counter` events of process 1.
+fn vector_clock_at(counter: u64) -> Result<VectorClock> {
+  // The owner of the clock and its number of entries, followed by its single entry.
+  let mut bytes = vec![WIRE_FORMAT_VERSION];
+  1usize.encode_into(&mut bytes);
+  1usize.encode_into(&mut bytes);
+  1usize.encode_into(&mut bytes);
+  counter.encode_into(&mut bytes);
+  VectorClock::from_bytes(&bytes)
+}
+
+#[test]
+fn writes_after_an_exhausted_counter_overflow() -> Result<()> {
+  let mut process = LamportProcess::with_members(0, [0, 1]);
+
+  let result = process.receive_message(Message {
+    vector_clock: vector_clock_at(u64::MAX)?,
+    key: "key".to_owned(),
+    value: Some("value".to_owned()),
+  });
+  assert!(matches!(result, Err(Error::CounterOverflow(_))));
+
+  // The write was not applied.
+  assert!(process.entry("key").is_err());
+  assert_eq!(0, process.vector_clock().clock(&1));
+  Ok(())
+}
+
+/// Sends a message from `sender` to `receiver` and compares the clock of the message with the
+/// clock of the receiver once it's received.
+fn exchange<T: LogicalClock>(sender: &mut T, receiver: &mut T) -> Result<CausalOrdering> {
+  let message = sender.send()?;
+  receiver.receive(&message)?;
+  Ok(message.compare(receiver))
+}
+
+#[test]
+fn received_messages_happen_before() -> Result<()> {
+  let (mut a, mut b) = (LamportClock::<usize, u64>::new(0), LamportClock::new(1));
+  a.tick()?;
+  a.tick()?;
+  assert_eq!(CausalOrdering::Before, exchange(&mut a, &mut b)?);
+  assert_eq!(3, a.counter());
+  assert_eq!(4, b.counter());
+
+  // A receiver ahead of the sender still moves past its own counter.
+  assert_eq!(CausalOrdering::Before, exchange(&mut a, &mut b)?);
+  assert_eq!(5, b.counter());
+
+  let (mut a, mut b) = (VectorClock::<usize, u64>::new(0), VectorClock::new(1));
+  assert_eq!(CausalOrdering::Before, exchange(&mut a, &mut b)?);
+  Ok(())
+}
+
+#[test]
+fn concurrent_events_are_ordered_by_process_id() -> Result<()> {
+  let (mut a, mut b) = (LamportClock::<usize, u64>::new(0), LamportClock::new(1));
+  a.tick()?;
+  b.tick()?;
+  assert_eq!(CausalOrdering::Before, a.compare(&b));
+  assert_eq!(CausalOrdering::After, b.compare(&a));
+  assert!(a < b);
+
+  b.tick()?;
+  assert_eq!(CausalOrdering::Before, a.compare(&b));
+  assert_eq!(CausalOrdering::Equal, a.compare(&a.clone()));
+
+  let (mut a, mut b) = (VectorClock::<usize, u64>::new(0), VectorClock::new(1));
+  a.increment_this_process_clock()?;
+  b.increment_this_process_clock()?;
+  assert_eq!(CausalOrdering::Concurrent, LogicalClock::compare(&a, &b));
+  Ok(())
+}
+
+#[test]
+fn overflows_leave_the_clock_untouched() -> Result<()> {
+  let mut bytes = vec![WIRE_FORMAT_VERSION];
+  0usize.encode_into(&mut bytes);
+  u64::MAX.encode_into(&mut bytes);
+  let mut exhausted = LamportClock::<usize, u64>::from_bytes(&bytes)?;
+
+  assert!(matches!(exhausted.tick(), Err(Error::CounterOverflow(_))));
+  assert_eq!(u64::MAX, exhausted.counter());
+
+  let mut other = LamportClock::new(1);
+  assert!(matches!(
+    other.receive(&exhausted),
+    Err(Error::CounterOverflow(_))
+  ));
+  assert_eq!(0, other.counter());
+  Ok(())
+}
+
+#[test]
+fn concurrent_values_are_resolved_by_the_greatest_clock() -> Result<()> {
+  let mut a = LamportProcess::with_members(0, [0, 1]);
+  let mut b = LamportProcess::with_members(1, [0, 1]);
+  a.put("key", "from 0".to_owned(), CausalContext::default())?;
+  b.put("key", "from 1".to_owned(), CausalContext::default())?;
+
+  a.merge_entry("key", b.entry("key")?)?;
+  b.merge_entry("key", a.entry("key")?)?;
+  for process in [&a, &b] {
+    assert_eq!(vec!["from 1".to_owned()], process.get("key")?.values);
+  }
+
+  // A write that had seen the value is ordered after it, whichever process coordinates it.
+  let context = a.get("key")?.context;
+  a.put("key", "from 0 again".to_owned(), context)?;
+  b.merge_entry("key", a.entry("key")?)?;
+  assert_eq!(vec!["from 0 again".to_owned()], b.get("key")?.values);
+  Ok(())
+}
+
+#[test]
+fn values_at_the_last_counter_are_never_superseded() -> Result<()> {
+  let mut a = LamportProcess::with_members(0, [0, 1]);
+  let mut b = LamportProcess::with_members(1, [0, 1]);
+
+  // A writer that had seen the next to last counter gets the last one.
+  a.receive_message(Message {
+    vector_clock: vector_clock_at(u64::MAX - 1)?,
+    key: "key".to_owned(),
+    value: Some("last".to_owned()),
+  })?;
+  b.merge_entry("key", a.entry("key")?)?;
+
+  // No clock is ordered after it, so writes that had seen it fail and writes that hadn't are kept
+  // next to it.
+  let context = b.get("key")?.context;
+  assert!(matches!(
+    b.put("key", "after".to_owned(), context),
+    Err(Error::CounterOverflow(_))
+  ));
+  b.put("key", "blind".to_owned(), CausalContext::default())?;
+  let result = b.get("key")?;
+  assert_eq!(vec!["last".to_owned(), "blind".to_owned()], result.values);
+  assert!(matches!(
+    b.put("key", "after".to_owned(), result.context),
+    Err(Error::CounterOverflow(_))
+  ));
+
+  // Other keys are not affected.
+  b.put("other key", "value".to_owned(), CausalContext::default())?;
+  assert_eq!(vec!["value".to_owned()], b.get("other key")?.values);
+  Ok(())
+}
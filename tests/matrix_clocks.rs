@@ -0,0 +1,75 @@
+use vector_clocks::{CausalContext, MatrixClock, Process, Result};
+
+#[test]
+fn received_clocks_teach_what_the_sender_knew() -> Result<()> {
+  let (mut a, mut b, mut c) = (
+    MatrixClock::<usize, u64>::new(0),
+    MatrixClock::new(1),
+    MatrixClock::new(2),
+  );
+  a.tick()?;
+  b.receive(&a)?;
+  assert_eq!(1, b.vector_clock().clock(&0));
+  assert_eq!(1, b.vector_clock().clock(&1));
+  assert_eq!(Some(a.vector_clock()), b.view(&0));
+  assert!(a.view(&1).is_none());
+
+  // c learns what a has seen through b, without hearing from a directly.
+  c.receive(&b)?;
+  assert_eq!(Some(a.vector_clock()), c.view(&0));
+  assert_eq!(Some(b.vector_clock()), c.view(&1));
+  assert_eq!(1, c.vector_clock().clock(&0));
+  Ok(())
+}
+
+#[test]
+fn stable_clocks_are_the_meet_of_every_view() -> Result<()> {
+  let (mut a, mut b, mut c) = (
+    MatrixClock::<usize, u64>::new(0),
+    MatrixClock::new(1),
+    MatrixClock::new(2),
+  );
+
+  // Nothing is stable until every process has been heard from.
+  a.tick()?;
+  assert_eq!(1, a.stable([]).clock(&0));
+  assert_eq!(0, a.stable([&1, &2]).clock(&0));
+
+  b.receive(&a)?;
+  c.receive(&a)?;
+  a.receive(&b)?;
+  assert_eq!(0, a.stable([&1, &2]).clock(&0));
+  a.receive(&c)?;
+
+  // Every process has seen the first event of a, but c hasn't seen the event of b.
+  let stable = a.stable([&1, &2]);
+  assert_eq!(1, stable.clock(&0));
+  assert_eq!(0, stable.clock(&1));
+  assert_eq!(0, stable.clock(&2));
+  assert_eq!(1, a.stable([&1]).clock(&1));
+  Ok(())
+}
+
+#[test]
+fn stable_clocks_collect_tombstones_every_process_has_seen() -> Result<()> {
+  let mut a: Process = Process::with_members(0, [0, 1]);
+  let mut b: Process = Process::with_members(1, [0, 1]);
+  let (mut a_clock, mut b_clock) = (MatrixClock::new(0), MatrixClock::new(1));
+
+  let context = a.put("key", "value".to_owned(), CausalContext::default())?;
+  a.delete("key", context)?;
+  a_clock.observe(a.vector_clock());
+
+  // b hasn't been heard from, so it may still hold the value.
+  assert_eq!(0, a.collect_stable_garbage(&a_clock.stable([&1]))?);
+  assert!(a.entry("key")?.is_deleted());
+
+  b.merge_entry("key", a.entry("key")?)?;
+  b_clock.observe(b.vector_clock());
+  b_clock.tick()?;
+  a_clock.receive(&b_clock)?;
+
+  assert_eq!(1, a.collect_stable_garbage(&a_clock.stable([&1]))?);
+  assert!(a.entry("key").is_err());
+  Ok(())
+}
@@ -0,0 +1,88 @@
+use vector_clocks::{CausalContext, Error, Membership, Message, Process, Result, VectorClock};
+
+#[test]
+fn every_change_starts_a_new_epoch() -> Result<()> {
+  let mut membership = Membership::new([0, 1]);
+  assert_eq!(0, membership.epoch());
+  assert_eq!(Some(0), membership.joined_in(&1));
+
+  assert_eq!(1, membership.add(2)?);
+  assert_eq!(Some(1), membership.joined_in(&2));
+
+  assert_eq!(2, membership.retire(&1)?);
+  assert!(!membership.is_member(&1) && membership.is_retired(&1));
+  assert_eq!(
+    (None, Some(2)),
+    (membership.joined_in(&1), membership.retired_in(&1))
+  );
+  assert_eq!(vec![&0, &2], membership.members().collect::<Vec<_>>());
+  Ok(())
+}
+
+#[test]
+fn retired_ids_are_never_reused() -> Result<()> {
+  let mut membership = Membership::new([0, 1]);
+  membership.retire(&1)?;
+
+  assert_eq!(
+    Err(Error::RetiredProcess("1".to_owned())),
+    membership.can_add(&1)
+  );
+  assert_eq!(
+    Err(Error::RetiredProcess("1".to_owned())),
+    membership.add(1)
+  );
+  assert_eq!(
+    Err(Error::DuplicateProcess("0".to_owned())),
+    membership.add(0)
+  );
+  assert_eq!(
+    Err(Error::UnknownProcess("1".to_owned())),
+    membership.retire(&1)
+  );
+
+  // Failed changes don't start a new epoch.
+  assert_eq!(1, membership.epoch());
+  assert!(membership.is_retired(&1) && !membership.is_member(&1));
+  Ok(())
+}
+
+#[test]
+fn retiring_a_process_compacts_clocks() -> Result<()> {
+  let mut clock: VectorClock = VectorClock::new(0);
+  clock.increment_process_clock(&0)?;
+  clock.increment_process_clock(&1)?;
+  clock.retain_processes(|process_id| *process_id != 1);
+  assert_eq!(vec![&0], clock.clocks().keys().collect::<Vec<_>>());
+
+  let mut a: Process = Process::with_members(0, [0, 1, 2]);
+  let mut b: Process = Process::with_members(1, [0, 1, 2]);
+  b.put("key", "from 1".to_owned(), CausalContext::default())?;
+  a.merge_entry("key", b.entry("key")?)?;
+  assert_eq!(1, a.vector_clock().clock(&1));
+  assert_eq!(1, a.entry("key")?.context().clock(&1));
+
+  a.retire_process(&1)?;
+  assert!(a.membership().is_retired(&1));
+  assert_eq!(0, a.vector_clock().clock(&1));
+  assert_eq!(0, a.entry("key")?.context().clock(&1));
+  assert_eq!(vec!["from 1".to_owned()], a.get("key")?.values);
+
+  // Messages received from now on are compacted too.
+  let mut context: VectorClock = VectorClock::new(2);
+  context.increment_process_clock(&1)?;
+  a.receive_message(Message {
+    vector_clock: context,
+    key: "other key".to_owned(),
+    value: Some("value".to_owned()),
+  })?;
+  assert_eq!(0, a.vector_clock().clock(&1));
+  assert_eq!(0, a.entry("other key")?.context().clock(&1));
+
+  assert_eq!(Err(Error::RetiredProcess("1".to_owned())), a.add_process(1));
+  assert_eq!(
+    Err(Error::InvalidProcessId("0".to_owned())),
+    a.retire_process(&0)
+  );
+  Ok(())
+}
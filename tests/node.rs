@@ -0,0 +1,95 @@
+use std::{
+  net::{SocketAddr, TcpListener},
+  process::{Child, Command, Stdio},
+  thread,
+  time::{Duration, Instant},
+};
+
+use vector_clocks::{CausalContext, Client, Result, Session};
+
+/// How long a node has to start or to replicate a write before the test fails.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A node binary running in the background, killed when dropped.
+struct Node(Child);
+
+impl Node {
+  fn spawn(process_id: usize, addresses: &[SocketAddr]) -> Self {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_node"));
+    command
+      .arg("serve")
+      .arg(process_id.to_string())
+      .arg(addresses[process_id].to_string())
+      .stderr(Stdio::null());
+    for (peer_id, address) in addresses.iter().enumerate() {
+      if peer_id != process_id {
+        command.arg(format!("{peer_id}={address}"));
+      }
+    }
+    Self(command.spawn().expect("the node binary should start"))
+  }
+}
+
+impl Drop for Node {
+  fn drop(&mut self) {
+    let _ = self.0.kill();
+    let _ = self.0.wait();
+  }
+}
+
+/// Returns addresses on the loopback interface that no process is listening on.
+fn free_addresses(count: usize) -> Vec<SocketAddr> {
+  let listeners: Vec<_> = (0..count)
+    .map(|_| TcpListener::bind("127.0.0.1:0").unwrap())
+    .collect();
+  listeners
+    .iter()
+    .map(|listener| listener.local_addr().unwrap())
+    .collect()
+}
+
+/// Retries `f` until it returns `Some` or the timeout expires.
+fn eventually<T>(mut f: impl FnMut() -> Option<T>) -> T {
+  let deadline = Instant::now() + TIMEOUT;
+  loop {
+    if let Some(value) = f() {
+      return value;
+    }
+    assert!(Instant::now() < deadline, "timed out");
+    thread::sleep(Duration::from_millis(20));
+  }
+}
+
+fn connect(address: SocketAddr) -> Client {
+  eventually(|| Client::connect(address).ok())
+}
+
+#[test]
+fn writes_are_replicated_between_nodes() -> Result<()> {
+  let addresses = free_addresses(2);
+  let _nodes: Vec<_> = (0..2).map(|id| Node::spawn(id, &addresses)).collect();
+  let mut clients: Vec<_> = addresses.iter().map(|address| connect(*address)).collect();
+
+  let context = clients[0].put("key", "value".to_owned(), CausalContext::default())?;
+  let result = eventually(|| {
+    let result = clients[1].get("key").ok()?;
+    (result.values == ["value"]).then_some(result)
+  });
+  assert_eq!(context, result.context);
+
+  // A write on the other node that had seen the value supersedes it on both.
+  clients[1].put("key", "other value".to_owned(), result.context)?;
+  eventually(|| (clients[0].get("key").ok()?.values == ["other value"]).then_some(()));
+
+  // A session read waits for the node to catch up with the writes of the session.
+  let mut session = Session::new();
+  clients[0].session_put(
+    "key",
+    "session value".to_owned(),
+    CausalContext::default(),
+    &mut session,
+  )?;
+  let result = clients[1].session_get("key", &mut session)?;
+  assert!(result.values.contains(&"session value".to_owned()));
+  Ok(())
+}
@@ -0,0 +1,106 @@
+use vector_clocks::{CausalContext, Error, Message, Process, Result, VectorClock};
+
+/// Returns a message writing `value` to `key` from a writer that had seen `vector_clock`.
+fn write(vector_clock: VectorClock, key: &str, value: &str) -> Message {
+  Message {
+    vector_clock,
+    key: key.to_owned(),
+    value: Some(value.to_owned()),
+  }
+}
+
+#[test]
+fn concurrent_writes_are_kept_as_siblings() -> Result<()> {
+  let mut a: Process = Process::with_members(0, [0, 1, 2]);
+  let mut b: Process = Process::with_members(1, [0, 1, 2]);
+  a.put("key", "from 0".to_owned(), CausalContext::default())?;
+  b.put("key", "from 1".to_owned(), CausalContext::default())?;
+
+  a.merge_entry("key", b.entry("key")?)?;
+  let entry = a.entry("key")?;
+  assert!(entry.has_siblings());
+  assert_eq!(
+    vec!["from 0", "from 1"],
+    entry.values().map(String::as_str).collect::<Vec<_>>()
+  );
+  assert_eq!(
+    (1, 1),
+    (entry.context().clock(&0), entry.context().clock(&1))
+  );
+
+  // A message from a writer that hadn't seen any of them adds a third sibling.
+  a.receive_message(write(VectorClock::new(2), "key", "from 2"))?;
+  assert_eq!(3, a.entry("key")?.siblings().len());
+
+  // A message from a writer that had seen every sibling supersedes all of them.
+  let context = a.entry("key")?.context();
+  a.receive_message(write(context, "key", "merged"))?;
+  let entry = a.entry("key")?;
+  assert!(!entry.has_siblings());
+  assert_eq!(vec!["merged".to_owned()], a.get("key")?.values);
+  Ok(())
+}
+
+#[test]
+fn stored_entries_always_have_a_sibling() -> Result<()> {
+  let mut process: Process = Process::with_members(0, [0, 1]);
+  let context = process.put("key", "value".to_owned(), CausalContext::default())?;
+  process.delete("key", context)?;
+
+  // A deleted key keeps its tombstone, so its context still has the version of the delete.
+  let entry = process.entry("key")?;
+  assert!(entry.is_deleted());
+  assert_eq!(1, entry.siblings().len());
+  assert_eq!(2, entry.context().clock(&0));
+
+  // Once the tombstone is purged the entry is removed instead of being left without siblings.
+  let tombstone = entry.context();
+  process.acknowledge(&1, "key", &tombstone)?;
+  assert_eq!(1, process.collect_garbage()?);
+  assert_eq!(
+    Some(Error::UnknownKey("key".to_owned())),
+    process.entry("key").err()
+  );
+  assert_eq!(0, process.keys().count());
+  Ok(())
+}
+
+#[test]
+fn puts_with_a_stale_context_keep_the_siblings() -> Result<()> {
+  let mut a: Process = Process::with_members(0, [0, 1]);
+  let mut b: Process = Process::with_members(1, [0, 1]);
+  let stale = a.put("key", "first".to_owned(), CausalContext::default())?;
+  b.put("key", "concurrent".to_owned(), CausalContext::default())?;
+  a.merge_entry("key", b.entry("key")?)?;
+
+  // The context of the first write hasn't seen the concurrent value, so only "first" is replaced.
+  a.put("key", "second".to_owned(), stale)?;
+  let mut values = a.get("key")?.values;
+  values.sort();
+  assert_eq!(vec!["concurrent".to_owned(), "second".to_owned()], values);
+  Ok(())
+}
+
+#[test]
+fn puts_with_the_context_of_a_read_collapse_the_siblings() -> Result<()> {
+  let mut a: Process = Process::with_members(0, [0, 1]);
+  let mut b: Process = Process::with_members(1, [0, 1]);
+  a.put("key", "from 0".to_owned(), CausalContext::default())?;
+  b.put("key", "from 1".to_owned(), CausalContext::default())?;
+  a.merge_entry("key", b.entry("key")?)?;
+
+  let read = a.get("key")?;
+  assert_eq!(2, read.values.len());
+  let context = a.put("key", "resolved".to_owned(), read.context)?;
+  assert_eq!(vec!["resolved".to_owned()], a.get("key")?.values);
+  assert!(!a.entry("key")?.has_siblings());
+
+  // The returned context subsumes the new value, so the next put replaces it too.
+  a.put("key", "latest".to_owned(), context)?;
+  assert_eq!(vec!["latest".to_owned()], a.get("key")?.values);
+
+  // The other process hadn't seen the resolution, so its copy is superseded by it.
+  b.merge_entry("key", a.entry("key")?)?;
+  assert_eq!(vec!["latest".to_owned()], b.get("key")?.values);
+  Ok(())
+}
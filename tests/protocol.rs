@@ -0,0 +1,179 @@
+mod common;
+
+use std::io::Cursor;
+
+use common::DvvProcess;
+use vector_clocks::{
+  read_frame, write_frame, CausalContext, DottedVersionVector, Error, Message, Process, Request,
+  Response, Result, Session, WireFormat, MAX_FRAME_LEN,
+};
+
+type DvvRequest = Request<usize, u64, DottedVersionVector>;
+
+/// Asserts that `value` decodes to a value with the same encoding.
+fn assert_round_trips<T: WireFormat>(value: &T) -> Result<()> {
+  let bytes = value.to_bytes();
+  assert_eq!(bytes, T::from_bytes(&bytes)?.to_bytes());
+  Ok(())
+}
+
+/// Returns a process that has written to `key`, and the session of the client that wrote it.
+fn written(key: &str) -> Result<(DvvProcess, Session)> {
+  let mut process = Process::with_members(0, [0, 1]);
+  let mut session = Session::new();
+  process.session_put(
+    key,
+    "value 1".to_owned(),
+    CausalContext::default(),
+    &mut session,
+  )?;
+  process.put(key, "value 2".to_owned(), CausalContext::default())?;
+  Ok((process, session))
+}
+
+#[test]
+fn requests_round_trip() -> Result<()> {
+  let (process, session) = written("key")?;
+  let context = process.get("key")?.context;
+
+  let requests: Vec<DvvRequest> = vec![
+    Request::Get {
+      key: "key".to_owned(),
+    },
+    Request::Put {
+      key: "key".to_owned(),
+      value: "value".to_owned(),
+      context: context.clone(),
+    },
+    Request::Delete {
+      key: "key".to_owned(),
+      context: CausalContext::default(),
+    },
+    Request::Message(Message {
+      vector_clock: process.vector_clock().clone(),
+      key: "key".to_owned(),
+      value: None,
+    }),
+    Request::Replicate {
+      key: "key".to_owned(),
+      entry: process.entry("key")?.clone(),
+    },
+    Request::SessionGet {
+      key: "key".to_owned(),
+      session: session.clone(),
+    },
+    Request::SessionPut {
+      key: "key".to_owned(),
+      value: "value".to_owned(),
+      context: context.clone(),
+      session: session.clone(),
+    },
+    Request::SessionDelete {
+      key: "key".to_owned(),
+      context,
+      session,
+    },
+  ];
+
+  for (tag, request) in requests.iter().enumerate() {
+    assert_eq!(tag as u8, request.to_bytes()[1]);
+    assert_round_trips(request)?;
+  }
+  Ok(())
+}
+
+#[test]
+fn responses_round_trip() -> Result<()> {
+  let (process, session) = written("key")?;
+  let result = process.get("key")?;
+
+  let responses: Vec<Response> = vec![
+    Response::Values(result.clone()),
+    Response::Context(result.context.clone()),
+    Response::Done,
+    Response::Error(Error::NotCaughtUp("key".to_owned())),
+    Response::SessionValues(result.clone(), session.clone()),
+    Response::SessionContext(result.context, session),
+  ];
+
+  for (tag, response) in responses.iter().enumerate() {
+    assert_eq!(tag as u8, response.to_bytes()[1]);
+    assert_round_trips(response)?;
+  }
+  Ok(())
+}
+
+#[test]
+fn errors_round_trip() -> Result<()> {
+  let errors = [
+    Error::UnknownProcess("1".to_owned()),
+    Error::DuplicateProcess("1".to_owned()),
+    Error::RetiredProcess("1".to_owned()),
+    Error::InvalidProcessId("1".to_owned()),
+    Error::UnknownKey("key".to_owned()),
+    Error::CounterOverflow("1".to_owned()),
+    Error::ClockDrift("drift".to_owned()),
+    Error::InvalidConfiguration("configuration".to_owned()),
+    Error::Io("io".to_owned()),
+    Error::QueueFull("queue".to_owned()),
+    Error::InvalidStamp("stamp".to_owned()),
+    Error::InvalidEncoding("encoding".to_owned()),
+    Error::NotCaughtUp("key".to_owned()),
+  ];
+
+  for (tag, error) in errors.iter().enumerate() {
+    let bytes = error.to_bytes();
+    assert_eq!(tag as u8, bytes[1]);
+    assert_eq!(*error, Error::from_bytes(&bytes)?);
+  }
+  Ok(())
+}
+
+#[test]
+fn frames_round_trip() -> Result<()> {
+  let mut stream = Vec::new();
+  write_frame(&mut stream, &Response::<usize, u64>::Done)?;
+  write_frame(&mut stream, &Error::UnknownKey("key".to_owned()))?;
+
+  let mut reader = Cursor::new(stream);
+  assert!(matches!(
+    read_frame::<Response>(&mut reader)?,
+    Some(Response::Done)
+  ));
+  assert_eq!(
+    Some(Error::UnknownKey("key".to_owned())),
+    read_frame(&mut reader)?
+  );
+  // The stream is closed between frames.
+  assert!(read_frame::<Response>(&mut reader)?.is_none());
+  Ok(())
+}
+
+#[test]
+fn frames_larger_than_the_maximum_are_rejected() {
+  let mut stream = (MAX_FRAME_LEN + 1).to_be_bytes().to_vec();
+  stream.extend_from_slice(&[0; 16]);
+
+  assert!(matches!(
+    read_frame::<Response>(&mut Cursor::new(stream)),
+    Err(Error::InvalidEncoding(_))
+  ));
+}
+
+#[test]
+fn truncated_frames_are_rejected() -> Result<()> {
+  let mut frame = Vec::new();
+  write_frame(&mut frame, &Response::<usize, u64>::Done)?;
+
+  // Closing the stream in the middle of the length prefix or of the payload is not a clean close.
+  for len in 1..frame.len() {
+    assert!(
+      matches!(
+        read_frame::<Response>(&mut Cursor::new(&frame[..len])),
+        Err(Error::Io(_))
+      ),
+      "frame truncated to {len} bytes"
+    );
+  }
+  Ok(())
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "serde")]
+
+use vector_clocks::{
+  CausalContext, DottedVersionVector, KvEntry, Message, Process, Result, VectorClock,
+};
+
+#[test]
+fn values_round_trip_through_serde() -> Result<()> {
+  let mut process: Process<usize, u64, _, DottedVersionVector> = Process::new(0);
+  process.put("key", "value 1".to_owned(), CausalContext::default())?;
+  process.put("key", "value 2".to_owned(), CausalContext::default())?;
+  let entry = process.entry("key")?;
+
+  let json = serde_json::to_string(entry).unwrap();
+  let decoded: KvEntry<usize, u64, DottedVersionVector> = serde_json::from_str(&json).unwrap();
+  assert_eq!(entry, &decoded);
+
+  let message: Message = Message {
+    vector_clock: entry.context(),
+    key: "key".to_owned(),
+    value: Some("value 3".to_owned()),
+  };
+  let json = serde_json::to_string(&message).unwrap();
+  let decoded: Message = serde_json::from_str(&json).unwrap();
+  assert_eq!(message.vector_clock, decoded.vector_clock);
+  assert_eq!(message.value, decoded.value);
+
+  let context = process.get("key")?.context;
+  let json = serde_json::to_string(&context).unwrap();
+  let decoded: CausalContext = serde_json::from_str(&json).unwrap();
+  assert_eq!(context, decoded);
+
+  let json = serde_json::to_string(&VectorClock::<usize, u64>::new(0)).unwrap();
+  assert_eq!(r#"{"process_id":0,"clocks":{}}"#, json);
+
+  Ok(())
+}
+
+#[test]
+fn zero_clocks_are_rejected() {
+  let decoded = serde_json::from_str::<VectorClock>(r#"{"process_id":0,"clocks":{"1":0}}"#);
+  assert!(decoded.is_err());
+
+  let decoded: VectorClock = serde_json::from_str(r#"{"process_id":0,"clocks":{"1":2}}"#).unwrap();
+  assert_eq!(2, decoded.clock(&1));
+}
+
+#[test]
+fn entries_without_siblings_are_rejected() {
+  let decoded = serde_json::from_str::<KvEntry>(r#"{"siblings":[],"acknowledgments":{}}"#);
+  assert!(decoded.is_err());
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::DvvProcess;
+use vector_clocks::{CausalContext, Error, Process, Result, Session};
+
+fn processes() -> (DvvProcess, DvvProcess) {
+  (
+    Process::with_members(0, [0, 1]),
+    Process::with_members(1, [0, 1]),
+  )
+}
+
+#[test]
+fn read_your_writes() -> Result<()> {
+  let (mut process_0, mut process_1) = processes();
+  let mut session = Session::new();
+
+  process_0.session_put(
+    "key",
+    "a".to_owned(),
+    CausalContext::default(),
+    &mut session,
+  )?;
+
+  assert_eq!(
+    Err(Error::NotCaughtUp("key".to_owned())),
+    process_1
+      .session_get("key", &mut session)
+      .map(|result| result.values)
+  );
+
+  process_1.merge_entry("key", process_0.entry("key")?)?;
+  assert_eq!(
+    vec!["a"],
+    process_1.session_get("key", &mut session)?.values
+  );
+  Ok(())
+}
+
+#[test]
+fn monotonic_reads() -> Result<()> {
+  let (mut process_0, mut process_1) = processes();
+  let context = process_0.put("key", "a".to_owned(), CausalContext::default())?;
+  process_1.merge_entry("key", process_0.entry("key")?)?;
+  process_0.put("key", "b".to_owned(), context)?;
+
+  let mut session = Session::new();
+  assert_eq!(
+    vec!["b"],
+    process_0.session_get("key", &mut session)?.values
+  );
+
+  // Process 1 still stores the value superseded by the one the session has read.
+  assert_eq!(vec!["a"], process_1.get("key")?.values);
+  assert!(!process_1.has_caught_up("key", &session));
+
+  process_1.merge_entry("key", process_0.entry("key")?)?;
+  assert_eq!(
+    vec!["b"],
+    process_1.session_get("key", &mut session)?.values
+  );
+
+  // Keys the session hasn't seen can be read from any process.
+  process_1.put("other", "c".to_owned(), CausalContext::default())?;
+  assert_eq!(
+    vec!["c"],
+    process_1.session_get("other", &mut session)?.values
+  );
+  assert!(!process_0.has_caught_up("other", &session));
+  Ok(())
+}
+
+#[test]
+fn deletes_are_seen_by_the_session() -> Result<()> {
+  let (mut process_0, mut process_1) = processes();
+  process_0.put("key", "a".to_owned(), CausalContext::default())?;
+  process_1.merge_entry("key", process_0.entry("key")?)?;
+
+  let mut session = Session::new();
+  let context = process_0.session_get("key", &mut session)?.context;
+  process_0.session_delete("key", context, &mut session)?;
+
+  // Process 1 would return the deleted value.
+  assert!(!process_1.has_caught_up("key", &session));
+
+  process_1.merge_entry("key", process_0.entry("key")?)?;
+  assert_eq!(
+    Err(Error::UnknownKey("key".to_owned())),
+    process_1
+      .session_get("key", &mut session)
+      .map(|result| result.values)
+  );
+  Ok(())
+}
+
+#[test]
+fn concurrent_writes_through_the_same_process_are_read() -> Result<()> {
+  let (mut process_0, mut process_1) = processes();
+  let mut session = Session::new();
+
+  // The second write doesn't supersede the first one, so the context it returns is the one it was
+  // given, but the session still records it.
+  process_0.put("key", "a".to_owned(), CausalContext::default())?;
+  process_0.session_put(
+    "key",
+    "b".to_owned(),
+    CausalContext::default(),
+    &mut session,
+  )?;
+
+  process_1.merge_entry("key", process_0.entry("key")?)?;
+  let mut values = process_1.session_get("key", &mut session)?.values;
+  values.sort();
+  assert_eq!(vec!["a", "b"], values);
+  Ok(())
+}
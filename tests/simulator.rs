@@ -0,0 +1,159 @@
+use vector_clocks::{
+  CausalContext, DottedVersionVector, LastWriterWins, NetworkConfig, Result, Simulator,
+};
+
+/// Returns the sorted values of `key` on every process.
+fn values<R, V>(simulator: &Simulator<R, V>, key: &str) -> Result<Vec<Vec<String>>>
+where
+  R: vector_clocks::ConflictResolver<usize, u64, V>,
+  V: vector_clocks::Version<usize, u64>,
+{
+  (0..simulator.processes().len())
+    .map(|process_id| {
+      let mut values = simulator.get(process_id, key)?.values;
+      values.sort();
+      Ok(values)
+    })
+    .collect()
+}
+
+/// Writes random values through random processes over a lossy network and returns what every
+/// process ends up storing.
+fn random_workload(seed: u64) -> Result<Vec<Vec<String>>> {
+  let network = NetworkConfig {
+    min_latency: 1,
+    max_latency: 50,
+    drop_probability: 0.2,
+    duplicate_probability: 0.2,
+  };
+  let mut simulator: Simulator = Simulator::new(seed, 5, network);
+
+  for i in 0..20 {
+    let process_id = i % 5;
+    let context = simulator
+      .get(process_id, "key")
+      .map(|result| result.context)
+      .unwrap_or_default();
+    simulator.put(process_id, "key", format!("value {i}"), context)?;
+    simulator.run_until(simulator.now() + 5)?;
+  }
+  simulator.run()?;
+
+  values(&simulator, "key")
+}
+
+#[test]
+fn runs_with_the_same_seed_are_identical() -> Result<()> {
+  for seed in 0..10 {
+    assert_eq!(random_workload(seed)?, random_workload(seed)?);
+  }
+  Ok(())
+}
+
+#[test]
+fn lossy_networks_converge_after_anti_entropy() -> Result<()> {
+  let network = NetworkConfig {
+    drop_probability: 0.5,
+    duplicate_probability: 0.5,
+    ..NetworkConfig::default()
+  };
+  let mut simulator: Simulator = Simulator::new(7, 4, network);
+
+  for process_id in 0..4 {
+    simulator.put(
+      process_id,
+      "key",
+      format!("value {process_id}"),
+      CausalContext::default(),
+    )?;
+  }
+  // Each round of anti-entropy loses half of its messages too.
+  for _ in 0..10 {
+    simulator.sync()?;
+    simulator.run()?;
+  }
+
+  let expected: Vec<String> = (0..4).map(|i| format!("value {i}")).collect();
+  assert_eq!(vec![expected; 4], values(&simulator, "key")?);
+  Ok(())
+}
+
+#[test]
+fn writes_on_both_sides_of_a_partition_become_siblings() -> Result<()> {
+  let mut simulator: Simulator<_, DottedVersionVector> =
+    Simulator::new(1, 3, NetworkConfig::default());
+
+  simulator.put(0, "key", "initial".to_owned(), CausalContext::default())?;
+  simulator.run()?;
+
+  simulator.partition([vec![0, 1], vec![2]]);
+  let context = simulator.get(0, "key")?.context;
+  simulator.put(0, "key", "majority".to_owned(), context)?;
+  let context = simulator.get(2, "key")?.context;
+  simulator.put(2, "key", "minority".to_owned(), context)?;
+  simulator.run()?;
+
+  assert_eq!(
+    vec![
+      vec!["majority".to_owned()],
+      vec!["majority".to_owned()],
+      vec!["minority".to_owned()],
+    ],
+    values(&simulator, "key")?
+  );
+
+  simulator.heal();
+  simulator.sync()?;
+  simulator.run()?;
+
+  let siblings = vec!["majority".to_owned(), "minority".to_owned()];
+  assert_eq!(vec![siblings; 3], values(&simulator, "key")?);
+
+  // A read on any process followed by a write collapses the siblings everywhere.
+  let context = simulator.get(1, "key")?.context;
+  simulator.put(1, "key", "resolved".to_owned(), context)?;
+  simulator.run()?;
+  assert_eq!(
+    vec![vec!["resolved".to_owned()]; 3],
+    values(&simulator, "key")?
+  );
+
+  Ok(())
+}
+
+#[test]
+fn last_writer_wins_is_decided_by_simulated_time() -> Result<()> {
+  let mut simulator =
+    Simulator::<_>::new(3, 2, NetworkConfig::default()).with_resolver(LastWriterWins);
+
+  simulator.partition([vec![0], vec![1]]);
+  simulator.put(1, "key", "early".to_owned(), CausalContext::default())?;
+  simulator.run_until(100)?;
+  simulator.put(0, "key", "late".to_owned(), CausalContext::default())?;
+
+  simulator.heal();
+  simulator.sync()?;
+  simulator.run()?;
+
+  assert_eq!(vec![vec!["late".to_owned()]; 2], values(&simulator, "key")?);
+  Ok(())
+}
+
+#[test]
+fn deliveries_past_the_end_of_time_are_delivered_at_the_end_of_time() -> Result<()> {
+  let network = NetworkConfig {
+    min_latency: u64::MAX,
+    max_latency: u64::MAX,
+    ..NetworkConfig::default()
+  };
+  let mut simulator: Simulator = Simulator::new(0, 2, network);
+  simulator.put(0, "key", "first".to_owned(), CausalContext::default())?;
+  simulator.run()?;
+  assert_eq!(u64::MAX, simulator.now());
+
+  simulator.put(1, "key", "second".to_owned(), CausalContext::default())?;
+  simulator.run()?;
+  assert_eq!(u64::MAX, simulator.now());
+  assert_eq!(vec![vec!["first", "second"]; 2], values(&simulator, "key")?);
+  Ok(())
+}
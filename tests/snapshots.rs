@@ -0,0 +1,127 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{assert_counts_past_the_restart, log_path, remove_log, state, LoggedProcess};
+use vector_clocks::{
+  CausalContext, CompactionPolicy, DottedVersionVector, Error, FsyncPolicy, Log, LogRecord,
+  Process, Result, WriteAheadLog,
+};
+
+fn open(path: &Path, compaction_policy: CompactionPolicy) -> Result<LoggedProcess> {
+  let log =
+    WriteAheadLog::open(path, FsyncPolicy::Always)?.with_compaction_policy(compaction_policy);
+  Process::with_members(0, [0, 1]).with_log(log)
+}
+
+/// Writes to a few keys, leaving siblings and tombstones behind.
+fn workload(process: &mut LoggedProcess) -> Result<()> {
+  for i in 0..20 {
+    let key = format!("key {}", i % 4);
+    let context = process
+      .get(&key)
+      .map(|result| result.context)
+      .unwrap_or_default();
+    process.put(&key, format!("value {i}"), context)?;
+  }
+
+  process.put("key 0", "sibling".to_owned(), CausalContext::default())?;
+  let context = process.get("key 1")?.context;
+  process.delete("key 1", context)?;
+  Ok(())
+}
+
+#[test]
+fn compaction_truncates_the_log() -> Result<()> {
+  let path = log_path("compaction");
+  let mut process = open(&path, CompactionPolicy::Records(5))?;
+  workload(&mut process)?;
+  let expected = state(&process);
+  assert!(process.log().snapshot_path().exists());
+  drop(process);
+
+  // 22 records were appended, the log was compacted before the 6th, 11th, 16th and 21st so it
+  // only holds the last 2: the sibling of "key 0" and the delete of "key 1".
+  let records: Vec<LogRecord<usize, u64, DottedVersionVector>> =
+    WriteAheadLog::open(&path, FsyncPolicy::Always)?.records()?;
+  let keys: Vec<_> = records
+    .iter()
+    .map(|record| match record {
+      LogRecord::Entry { key, .. } | LogRecord::Remove { key } => key.as_str(),
+      LogRecord::ProcessId { .. } => panic!("the process didn't roll over"),
+    })
+    .collect();
+  assert_eq!(vec!["key 0", "key 1"], keys);
+
+  let mut process = open(&path, CompactionPolicy::Records(5))?;
+  assert_eq!(expected, state(&process));
+  assert_eq!(2, process.get("key 0")?.values.len());
+  assert!(process.entry("key 1")?.is_deleted());
+  assert_counts_past_the_restart(&mut process)?;
+
+  remove_log(&path)?;
+  Ok(())
+}
+
+#[test]
+fn compaction_by_len() -> Result<()> {
+  let path = log_path("len");
+  let mut process = open(&path, CompactionPolicy::Len(256))?;
+  workload(&mut process)?;
+  let expected = state(&process);
+  drop(process);
+
+  assert!(fs::metadata(&path)?.len() < 512);
+  assert_eq!(expected, state(&open(&path, CompactionPolicy::Len(256))?));
+
+  remove_log(&path)?;
+  Ok(())
+}
+
+#[test]
+fn crash_before_the_log_is_truncated() -> Result<()> {
+  let path = log_path("crash");
+  let mut process = open(&path, CompactionPolicy::Never)?;
+  workload(&mut process)?;
+  let expected = state(&process);
+
+  // The snapshot is saved but the process stops before truncating the log, whose records are
+  // replayed on top of it.
+  process.snapshot().save(process.log().snapshot_path())?;
+  drop(process);
+
+  let process = open(&path, CompactionPolicy::Never)?;
+  assert_eq!(expected, state(&process));
+
+  remove_log(&path)?;
+  Ok(())
+}
+
+#[test]
+fn corrupted_snapshots_are_not_loaded() -> Result<()> {
+  let path = log_path("corrupted_snapshot");
+  let mut process = open(&path, CompactionPolicy::Never)?;
+  workload(&mut process)?;
+  process.compact()?;
+  let snapshot_path = process.log().snapshot_path().to_owned();
+  drop(process);
+
+  let mut snapshot = fs::read(&snapshot_path)?;
+  let middle = snapshot.len() / 2;
+  snapshot[middle] ^= 0x01;
+  fs::write(&snapshot_path, &snapshot)?;
+
+  assert!(matches!(
+    open(&path, CompactionPolicy::Never),
+    Err(Error::InvalidEncoding(_))
+  ));
+
+  fs::write(&snapshot_path, &snapshot[..3])?;
+  assert!(matches!(
+    open(&path, CompactionPolicy::Never),
+    Err(Error::InvalidEncoding(_))
+  ));
+
+  remove_log(&path)?;
+  Ok(())
+}
@@ -0,0 +1,72 @@
+use vector_clocks::{CausalContext, Error, Message, Process, Result};
+
+#[test]
+fn tombstones_keep_superseded_values_from_coming_back() -> Result<()> {
+  let mut process: Process = Process::with_members(0, [0, 1]);
+  let context = process.put("key", "value".to_owned(), CausalContext::default())?;
+  let stale = process.entry("key")?.context();
+
+  process.delete("key", context)?;
+  assert!(matches!(process.get("key"), Err(Error::UnknownKey(_))));
+
+  // A write concurrent with the delete is kept next to the tombstone, but the value both of them
+  // superseded is not brought back.
+  process.receive_message(Message {
+    vector_clock: stale,
+    key: "key".to_owned(),
+    value: Some("concurrent".to_owned()),
+  })?;
+  assert_eq!(vec!["concurrent".to_owned()], process.get("key")?.values);
+  assert!(process
+    .entry("key")?
+    .siblings()
+    .iter()
+    .any(|sibling| sibling.is_tombstone()));
+  Ok(())
+}
+
+#[test]
+fn tombstones_keep_stale_copies_from_resurrecting_values() -> Result<()> {
+  let mut a: Process = Process::with_members(0, [0, 1]);
+  let mut b: Process = Process::with_members(1, [0, 1]);
+  let context = a.put("key", "value".to_owned(), CausalContext::default())?;
+  b.merge_entry("key", a.entry("key")?)?;
+  let stale = b.entry("key")?.clone();
+
+  a.delete("key", context.clone())?;
+  a.merge_entry("key", &stale)?;
+  assert!(matches!(a.get("key"), Err(Error::UnknownKey(_))));
+
+  b.put("key", "concurrent".to_owned(), context)?;
+  a.merge_entry("key", b.entry("key")?)?;
+  assert_eq!(vec!["concurrent".to_owned()], a.get("key")?.values);
+  Ok(())
+}
+
+#[test]
+fn tombstones_are_purged_once_every_member_acknowledged_them() -> Result<()> {
+  let mut process: Process = Process::with_members(0, [0, 1, 2]);
+  let context = process.put("key", "value".to_owned(), CausalContext::default())?;
+  let before_delete = process.entry("key")?.context();
+  process.delete("key", context)?;
+  let tombstone = process.entry("key")?.context();
+  assert_eq!(0, process.collect_garbage()?);
+
+  process.acknowledge(&1, "key", &tombstone)?;
+  assert_eq!(0, process.collect_garbage()?);
+
+  // An acknowledgment that doesn't cover the delete doesn't count.
+  process.acknowledge(&2, "key", &before_delete)?;
+  assert_eq!(0, process.collect_garbage()?);
+  assert!(process.entry("key")?.is_deleted());
+
+  process.acknowledge(&2, "key", &tombstone)?;
+  assert_eq!(1, process.collect_garbage()?);
+  assert!(matches!(process.entry("key"), Err(Error::UnknownKey(_))));
+
+  assert!(matches!(
+    process.acknowledge(&3, "key", &tombstone),
+    Err(Error::UnknownProcess(_))
+  ));
+  Ok(())
+}
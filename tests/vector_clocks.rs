@@ -0,0 +1,192 @@
+use std::cmp::Ordering;
+
+use vector_clocks::{CausalOrdering, Result, Rng, VectorClock};
+
+/// Returns a clock owned by `process_id` that has seen `events` events of each process.
+fn clock(process_id: usize, events: &[(usize, u64)]) -> Result<VectorClock> {
+  let mut clock = VectorClock::new(process_id);
+  for (process_id, events) in events {
+    for _ in 0..*events {
+      clock.increment_process_clock(process_id)?;
+    }
+  }
+  Ok(clock)
+}
+
+#[test]
+fn clocks_compare_by_their_entries() -> Result<()> {
+  // Clocks of different owners that have seen the same events are equal.
+  let a = clock(0, &[(0, 1), (1, 2)])?;
+  let b = clock(1, &[(0, 1), (1, 2)])?;
+  assert_eq!(CausalOrdering::Equal, a.compare(&b));
+  assert_eq!(a, b);
+  assert_eq!(Some(Ordering::Equal), a.partial_cmp(&b));
+  assert!(a.dominates(&b) && b.dominates(&a));
+  assert!(!a.happened_before(&b) && !a.is_concurrent_with(&b));
+
+  let c = clock(2, &[(0, 1), (1, 3)])?;
+  assert_eq!(CausalOrdering::Before, a.compare(&c));
+  assert_eq!(CausalOrdering::After, c.compare(&a));
+  assert!(a.happened_before(&c) && !c.happened_before(&a));
+  assert!(c.dominates(&a) && !a.dominates(&c));
+  assert_eq!(Some(Ordering::Less), a.partial_cmp(&c));
+  assert!(a < c);
+  assert_eq!(Some(Ordering::Greater), c.partial_cmp(&a));
+  assert_ne!(a, c);
+
+  let d = clock(3, &[(0, 2), (1, 2)])?;
+  assert_eq!(CausalOrdering::Concurrent, c.compare(&d));
+  assert_eq!(CausalOrdering::Concurrent, d.compare(&c));
+  assert!(c.is_concurrent_with(&d));
+  assert!(!c.happened_before(&d) && !d.happened_before(&c));
+  assert!(!c.dominates(&d) && !d.dominates(&c));
+  assert_eq!(None, c.partial_cmp(&d));
+  Ok(())
+}
+
+#[test]
+fn received_messages_happen_before_the_receiver() -> Result<()> {
+  let mut sender = clock(0, &[(0, 1)])?;
+  let mut receiver = clock(1, &[(1, 1)])?;
+  assert!(sender.is_concurrent_with(&receiver));
+
+  let message = sender.clone();
+  receiver.message_received(&message)?;
+  assert!(message.happened_before(&receiver));
+  assert_eq!(2, receiver.clock(&1));
+
+  // The sender hasn't heard back, so its next event is concurrent with the receipt.
+  sender.increment_this_process_clock()?;
+  assert_eq!(CausalOrdering::Concurrent, sender.compare(&receiver));
+  Ok(())
+}
+
+/// Returns a clock that has seen up to 3 events of each of the processes `0..4`.
+fn random_clock(rng: &mut Rng) -> Result<VectorClock> {
+  let events: Vec<_> = (0..4)
+    .map(|process_id| (process_id, rng.between(0, 3)))
+    .collect();
+  clock(rng.below(4), &events)
+}
+
+#[test]
+fn join_and_meet_leave_their_inputs_untouched() -> Result<()> {
+  let a = clock(0, &[(0, 2), (1, 1)])?;
+  let b = clock(1, &[(1, 3), (2, 1)])?;
+  let (a_before, b_before) = (a.clocks().clone(), b.clocks().clone());
+
+  let join = a.join(&b);
+  let meet = a.meet(&b);
+  assert_eq!(&a_before, a.clocks());
+  assert_eq!(&b_before, b.clocks());
+  assert_eq!(&0, join.process_id());
+  assert_eq!(&0, meet.process_id());
+
+  // Neither of them counts as an event.
+  assert_eq!(clock(0, &[(0, 2), (1, 3), (2, 1)])?.clocks(), join.clocks());
+  assert_eq!(clock(0, &[(1, 1)])?.clocks(), meet.clocks());
+
+  let mut in_place = a.clone();
+  in_place.join_in_place(&b);
+  assert_eq!(join.clocks(), in_place.clocks());
+  let mut in_place = a.clone();
+  in_place.meet_in_place(&b);
+  assert_eq!(meet.clocks(), in_place.clocks());
+  Ok(())
+}
+
+#[test]
+fn join_and_meet_are_commutative_associative_and_idempotent() -> Result<()> {
+  let mut rng = Rng::new(3);
+  for _ in 0..500 {
+    let (a, b, c) = (
+      random_clock(&mut rng)?,
+      random_clock(&mut rng)?,
+      random_clock(&mut rng)?,
+    );
+
+    assert_eq!(a.join(&b), b.join(&a));
+    assert_eq!(a.meet(&b), b.meet(&a));
+    assert_eq!(a.join(&b).join(&c), a.join(&b.join(&c)));
+    assert_eq!(a.meet(&b).meet(&c), a.meet(&b.meet(&c)));
+    assert_eq!(a, a.join(&a));
+    assert_eq!(a, a.meet(&a));
+
+    // The join has seen both clocks and the meet has been seen by both of them.
+    assert!(a.join(&b).dominates(&a) && a.join(&b).dominates(&b));
+    assert!(a.dominates(&a.meet(&b)) && b.dominates(&a.meet(&b)));
+    assert_eq!(a, a.join(&a.meet(&b)));
+    assert_eq!(a, a.meet(&a.join(&b)));
+  }
+  Ok(())
+}
+
+#[test]
+fn missing_entries_compare_as_zero() -> Result<()> {
+  // A clock that has only heard of process 0 against one that has heard of 3 processes.
+  let small = clock(0, &[(0, 2)])?;
+  let large = clock(1, &[(0, 2), (1, 1), (5, 4)])?;
+  assert_eq!(0, small.clock(&5));
+  assert_eq!(CausalOrdering::Before, small.compare(&large));
+  assert_eq!(CausalOrdering::After, large.compare(&small));
+
+  // An entry the other clock doesn't have is greater than its zero.
+  let other = clock(2, &[(0, 1), (7, 1)])?;
+  assert_eq!(CausalOrdering::Concurrent, other.compare(&large));
+  assert_eq!(CausalOrdering::Concurrent, large.compare(&other));
+  Ok(())
+}
+
+#[test]
+fn join_grows_clocks_and_meet_shrinks_them() -> Result<()> {
+  let small = clock(0, &[(0, 2)])?;
+  let large = clock(1, &[(0, 1), (1, 1), (5, 4)])?;
+
+  let join = small.join(&large);
+  assert_eq!(vec![&0, &1, &5], join.clocks().keys().collect::<Vec<_>>());
+  assert_eq!((2, 1, 4), (join.clock(&0), join.clock(&1), join.clock(&5)));
+
+  // Processes one of the clocks hasn't heard of are zero in the meet, so they have no entry.
+  let meet = large.meet(&small);
+  assert_eq!(vec![(&0, &1)], meet.clocks().iter().collect::<Vec<_>>());
+  assert_eq!(meet, small.meet(&large));
+  Ok(())
+}
+
+#[test]
+fn clocks_are_keyed_by_any_process_id() -> Result<()> {
+  let (alice, bob) = ("alice".to_owned(), "bob".to_owned());
+  let mut a: VectorClock<String> = VectorClock::new(alice.clone());
+  let mut b: VectorClock<String> = VectorClock::new(bob.clone());
+  a.increment_this_process_clock()?;
+  b.message_received(&a)?;
+
+  assert_eq!(&bob, b.process_id());
+  assert_eq!((1, 1), (b.clock(&alice), b.clock(&bob)));
+  assert!(a.happened_before(&b));
+
+  let mut c = VectorClock::new("carol".to_owned());
+  c.increment_this_process_clock()?;
+  assert!(b.is_concurrent_with(&c));
+  Ok(())
+}
+
+#[test]
+fn zero_entries_are_not_stored() -> Result<()> {
+  let a = VectorClock::<String>::new("alice".to_owned());
+  assert!(a.clocks().is_empty());
+  assert_eq!(0, a.clock(&"bob".to_owned()));
+  assert!(a.clocks().is_empty());
+
+  // Joining a clock without events, or meeting clocks that share no process, adds no entries.
+  let mut b = VectorClock::new("bob".to_owned());
+  b.increment_this_process_clock()?;
+  assert_eq!(1, b.join(&a).clocks().len());
+  assert!(b.meet(&a).clocks().is_empty());
+
+  let mut c = VectorClock::new("carol".to_owned());
+  c.increment_this_process_clock()?;
+  assert!(b.meet(&c).clocks().is_empty());
+  assert_eq!(CausalOrdering::Equal, b.meet(&c).compare(&a));
+  Ok(())
+}
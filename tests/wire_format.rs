@@ -0,0 +1,137 @@
+use vector_clocks::{
+  CausalContext, DottedVersionVector, Error, Incarnation, KvEntry, Message, Process, Result,
+  VectorClock, WireFormat, WIRE_FORMAT_VERSION,
+};
+
+fn vector_clock<P: vector_clocks::ProcessId, C: vector_clocks::Counter>(
+  process_id: P,
+  events: &[P],
+) -> Result<VectorClock<P, C>> {
+  let mut vector_clock = VectorClock::new(process_id);
+  for process_id in events {
+    vector_clock.increment_process_clock(process_id)?;
+  }
+  Ok(vector_clock)
+}
+
+/// Returns an entry with two concurrent values, a tombstone and an acknowledgment.
+fn sample_entry<V: vector_clocks::Version<usize, u64>>() -> Result<KvEntry<usize, u64, V>> {
+  let mut process: Process<usize, u64, _, V> = Process::with_members(0, [1]);
+  process.put("key", "value 1".to_owned(), CausalContext::default())?;
+  let context = process.put("key", "value 2".to_owned(), CausalContext::default())?;
+  process.delete("key", context)?;
+  process.acknowledge(&1, "key", &process.entry("key")?.context())?;
+  Ok(process.entry("key")?.clone())
+}
+
+#[test]
+fn vector_clocks_round_trip() -> Result<()> {
+  let clock: VectorClock = vector_clock(0, &[0, 0, 1, 300])?;
+  assert_eq!(clock, VectorClock::from_bytes(&clock.to_bytes())?);
+
+  let clock: VectorClock<String, u32> =
+    vector_clock("a".to_owned(), &["a".to_owned(), "b".to_owned()])?;
+  let decoded = VectorClock::<String, u32>::from_bytes(&clock.to_bytes())?;
+  assert_eq!(clock, decoded);
+  assert_eq!(clock.process_id(), decoded.process_id());
+
+  let clock: VectorClock<Incarnation<String>> = vector_clock(
+    Incarnation::first("a".to_owned()),
+    &[Incarnation::first("a".to_owned()).next()],
+  )?;
+  assert_eq!(
+    clock,
+    VectorClock::<Incarnation<String>>::from_bytes(&clock.to_bytes())?
+  );
+
+  Ok(())
+}
+
+#[test]
+fn messages_round_trip() -> Result<()> {
+  for value in [Some("value".to_owned()), None] {
+    let message: Message = Message {
+      vector_clock: vector_clock(1, &[0, 1])?,
+      key: "key".to_owned(),
+      value,
+    };
+    let decoded = Message::from_bytes(&message.to_bytes())?;
+    assert_eq!(message.vector_clock, decoded.vector_clock);
+    assert_eq!(message.key, decoded.key);
+    assert_eq!(message.value, decoded.value);
+  }
+
+  Ok(())
+}
+
+#[test]
+fn entries_round_trip() -> Result<()> {
+  let entry: KvEntry = sample_entry()?;
+  let decoded = KvEntry::from_bytes(&entry.to_bytes())?;
+  assert_eq!(entry, decoded);
+  assert_eq!(entry.to_bytes(), decoded.to_bytes());
+
+  let entry: KvEntry<usize, u64, DottedVersionVector> = sample_entry()?;
+  let decoded = KvEntry::<usize, u64, DottedVersionVector>::from_bytes(&entry.to_bytes())?;
+  assert_eq!(entry, decoded);
+  assert_eq!(entry.to_bytes(), decoded.to_bytes());
+
+  let context: CausalContext = CausalContext::default();
+  assert_eq!(context, CausalContext::from_bytes(&context.to_bytes())?);
+
+  Ok(())
+}
+
+/// Bytes written by version 1 of the format must keep decoding to the same values.
+#[test]
+fn version_1_encodings_still_decode() -> Result<()> {
+  // Owned by process 1, {0: 3, 300: 1}.
+  let bytes = [1, 1, 2, 0, 3, 0xac, 0x02, 1];
+  let clock: VectorClock = vector_clock(1, &[0, 0, 0, 300])?;
+  assert_eq!(clock, VectorClock::from_bytes(&bytes)?);
+  assert_eq!(bytes.to_vec(), clock.to_bytes());
+
+  // A delete of "k" with the clock {0: 1} owned by process 0.
+  let bytes = [1, 0, 1, 0, 1, 1, b'k', 0];
+  let message: Message = Message::from_bytes(&bytes)?;
+  assert_eq!(vector_clock::<usize, u64>(0, &[0])?, message.vector_clock);
+  assert_eq!("k", message.key);
+  assert_eq!(None, message.value);
+  assert_eq!(bytes.to_vec(), message.to_bytes());
+
+  Ok(())
+}
+
+#[test]
+fn invalid_encodings_are_rejected() -> Result<()> {
+  let bytes = vector_clock::<usize, u64>(1, &[0, 1])?.to_bytes();
+  let invalid = |bytes: &[u8]| {
+    matches!(
+      VectorClock::<usize, u64>::from_bytes(bytes),
+      Err(Error::InvalidEncoding(_))
+    )
+  };
+
+  // Written by a newer version of the format.
+  let mut newer = bytes.clone();
+  newer[0] = WIRE_FORMAT_VERSION + 1;
+  assert!(invalid(&newer));
+
+  assert!(invalid(&[]));
+  assert!(invalid(&bytes[..bytes.len() - 1]));
+  assert!(invalid(&[bytes.as_slice(), &[0]].concat()));
+  // Entries out of order and zero entries.
+  assert!(invalid(&[1, 1, 2, 1, 1, 0, 1]));
+  assert!(invalid(&[1, 1, 1, 0, 0]));
+  // A varint longer than 64 bits.
+  assert!(invalid(&[
+    1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 0
+  ]));
+  // A u32 counter that doesn't fit.
+  assert!(matches!(
+    VectorClock::<usize, u32>::from_bytes(&[1, 1, 1, 0, 0x80, 0x80, 0x80, 0x80, 0x10]),
+    Err(Error::InvalidEncoding(_))
+  ));
+
+  Ok(())
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{
+  assert_counts_past_the_restart, log_path, open, remove_log, state, DvvProcess, LoggedProcess,
+};
+use vector_clocks::{
+  CausalContext, Error, FsyncPolicy, Incarnation, KeepSiblings, Process, Result, SystemClock,
+  VectorClock, WriteAheadLog,
+};
+
+type Id = Incarnation<usize>;
+type IncarnationProcess =
+  Process<Id, u64, KeepSiblings, VectorClock<Id>, SystemClock, WriteAheadLog>;
+
+/// Runs writes, deletes, merges and garbage collection on `process`, calling `checkpoint` after
+/// each of them.
+fn workload(process: &mut LoggedProcess, mut checkpoint: impl FnMut(&LoggedProcess)) -> Result<()> {
+  let mut other: DvvProcess = Process::with_members(1, [0, 1]);
+
+  let context = process.put("key", "a".to_owned(), CausalContext::default())?;
+  checkpoint(process);
+  process.put("key", "b".to_owned(), CausalContext::default())?;
+  checkpoint(process);
+  process.put("key", "c".to_owned(), context)?;
+  checkpoint(process);
+
+  other.put("key", "d".to_owned(), CausalContext::default())?;
+  process.merge_entry("key", other.entry("key")?)?;
+  checkpoint(process);
+
+  let context = process.put("gone", "e".to_owned(), CausalContext::default())?;
+  checkpoint(process);
+  process.delete("gone", context)?;
+  checkpoint(process);
+  let acknowledged = process.entry("gone")?.context();
+  process.acknowledge(&1, "gone", &acknowledged)?;
+  checkpoint(process);
+  assert_eq!(1, process.collect_garbage()?);
+  checkpoint(process);
+
+  let context = process.get("key")?.context;
+  process.put("key", "f".to_owned(), context)?;
+  checkpoint(process);
+  Ok(())
+}
+
+#[test]
+fn replay_restores_values_and_clock() -> Result<()> {
+  for fsync_policy in [
+    FsyncPolicy::Always,
+    FsyncPolicy::Every(3),
+    FsyncPolicy::Never,
+  ] {
+    let path = log_path(&format!("replay_{fsync_policy:?}"));
+
+    let mut process = open(&path, fsync_policy)?;
+    workload(&mut process, |_| {})?;
+    let expected = state(&process);
+    drop(process);
+
+    let mut process = open(&path, fsync_policy)?;
+    assert_eq!(expected, state(&process));
+    assert!(process.entry("gone").is_err());
+    assert_counts_past_the_restart(&mut process)?;
+
+    remove_log(&path)?;
+  }
+  Ok(())
+}
+
+#[test]
+fn truncated_logs_recover_the_last_complete_record() -> Result<()> {
+  let path = log_path("truncated");
+
+  // The state of the process and the length of its log after each change.
+  let mut checkpoints = Vec::new();
+  let mut process = open(&path, FsyncPolicy::Always)?;
+  checkpoints.push((0, state(&process)));
+  workload(&mut process, |process| {
+    checkpoints.push((fs::metadata(&path).unwrap().len(), state(process)));
+  })?;
+  drop(process);
+  let log = fs::read(&path)?;
+
+  let truncated_path = log_path("truncated_copy");
+  for len in 0..=log.len() {
+    fs::write(&truncated_path, &log[..len])?;
+
+    let (_, expected) = checkpoints
+      .iter()
+      .rev()
+      .find(|(checkpoint_len, _)| *checkpoint_len as usize <= len)
+      .unwrap();
+
+    let mut process = open(&truncated_path, FsyncPolicy::Always)?;
+    assert_eq!(*expected, state(&process), "log truncated to {len} bytes");
+
+    // The torn record is gone, so records appended after recovering are replayed too.
+    let counter = process.vector_clock().clock(&0);
+    process.put("key", "after crash".to_owned(), CausalContext::default())?;
+    let after_crash = state(&process);
+    assert_eq!(counter + 1, after_crash.0.clock(&0));
+    drop(process);
+
+    let process = open(&truncated_path, FsyncPolicy::Always)?;
+    assert_eq!(after_crash, state(&process), "log truncated to {len} bytes");
+  }
+
+  remove_log(&path)?;
+  remove_log(&truncated_path)?;
+  Ok(())
+}
+
+#[test]
+fn corrupted_records_are_rejected() -> Result<()> {
+  let path = log_path("corrupted");
+  let mut lens = Vec::new();
+  let mut process = open(&path, FsyncPolicy::Always)?;
+  workload(&mut process, |_| {
+    lens.push(fs::metadata(&path).unwrap().len())
+  })?;
+  drop(process);
+
+  // Flips a byte of the value of the second record, past its header.
+  let mut log = fs::read(&path)?;
+  log[lens[0] as usize + 12 + 4] ^= 0xff;
+  fs::write(&path, &log)?;
+
+  assert!(matches!(
+    open(&path, FsyncPolicy::Always),
+    Err(Error::InvalidEncoding(_))
+  ));
+
+  remove_log(&path)?;
+  Ok(())
+}
+
+#[test]
+fn corrupted_lengths_are_rejected() -> Result<()> {
+  let path = log_path("corrupted_length");
+  let mut process = open(&path, FsyncPolicy::Always)?;
+  workload(&mut process, |_| {})?;
+  drop(process);
+  let log = fs::read(&path)?;
+
+  // Whether the length now runs past the end of the file or not, the records that follow it are
+  // kept instead of being truncated as a torn record.
+  for flipped in [0x80, 0x01] {
+    let mut corrupted = log.clone();
+    corrupted[0] ^= flipped;
+    fs::write(&path, &corrupted)?;
+
+    assert!(matches!(
+      open(&path, FsyncPolicy::Always),
+      Err(Error::InvalidEncoding(_))
+    ));
+    assert_eq!(log.len() as u64, fs::metadata(&path)?.len());
+  }
+
+  remove_log(&path)?;
+  Ok(())
+}
+
+#[test]
+fn roll_overs_are_replayed() -> Result<()> {
+  let first = Incarnation::first(0);
+  let open = |path: &Path| -> Result<IncarnationProcess> {
+    Process::with_members(first, [first]).with_log(WriteAheadLog::open(path, FsyncPolicy::Always)?)
+  };
+
+  for compacted in [false, true] {
+    let path = log_path(&format!("roll_over_{compacted}"));
+    let mut process = open(&path)?;
+    process.put("key", "a".to_owned(), CausalContext::default())?;
+    process.roll_over()?;
+    let context = process.put("key", "b".to_owned(), CausalContext::default())?;
+    if compacted {
+      process.compact()?;
+    }
+    drop(process);
+
+    let mut process = open(&path)?;
+    let next = first.next();
+    assert_eq!(&next, process.process_id());
+    assert_eq!(&next, process.vector_clock().process_id());
+    assert!(process.membership().is_member(&first));
+    assert!(process.membership().is_member(&next));
+
+    // Writes keep being counted by the incarnation the process rolled over to.
+    process.put("key", "c".to_owned(), context)?;
+    assert_eq!(1, process.vector_clock().clock(&first));
+    assert_eq!(2, process.vector_clock().clock(&next));
+    drop(process);
+    assert_eq!(&next, open(&path)?.process_id());
+
+    remove_log(&path)?;
+  }
+  Ok(())
+}
+
+#[test]
+fn roll_overs_to_retired_incarnations_are_rejected() -> Result<()> {
+  let (first, next) = (Incarnation::first(0), Incarnation::first(0).next());
+  let open = |path: &Path| -> Result<IncarnationProcess> {
+    let mut process = Process::with_members(first, [first, next]);
+    process.retire_process(&next)?;
+    process.with_log(WriteAheadLog::open(path, FsyncPolicy::Always)?)
+  };
+
+  let path = log_path("retired_roll_over");
+  let mut process = open(&path)?;
+  process.put("key", "a".to_owned(), CausalContext::default())?;
+  let len = fs::metadata(&path)?.len();
+  assert_eq!(
+    Err(Error::RetiredProcess(format!("{next:?}"))),
+    process.roll_over()
+  );
+  assert_eq!(&first, process.process_id());
+  assert_eq!(len, fs::metadata(&path)?.len());
+  drop(process);
+  remove_log(&path)?;
+
+  // A log recording a roll over to an incarnation that has since been retired is not replayed.
+  let mut process: IncarnationProcess = Process::with_members(first, [first])
+    .with_log(WriteAheadLog::open(&path, FsyncPolicy::Always)?)?;
+  process.roll_over()?;
+  drop(process);
+  assert!(matches!(open(&path), Err(Error::RetiredProcess(_))));
+
+  remove_log(&path)?;
+  Ok(())
+}